@@ -0,0 +1,223 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Index;
+
+use crate::ast::*;
+use crate::index::uid::{fresh_uid, UID};
+
+/// Handle to a node interned in a `TermArena`.
+///
+/// Two handles from the same arena are equal iff they denote structurally equal terms.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct TermId(usize);
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Node<T: IdentType> {
+    Var(T),
+    App(TermId, TermId),
+    Abs(T, TermId, TermId),
+    Pi(T, TermId, TermId),
+    Kind(Kinds),
+}
+
+/// Hash-consed term storage. Every distinct subterm is stored exactly once.
+#[derive(Clone, Debug)]
+pub struct TermArena<T: IdentType> {
+    nodes: Vec<Node<T>>,
+    interned: HashMap<Node<T>, TermId>,
+}
+
+impl<T: IdentType> Default for TermArena<T> {
+    fn default() -> Self {
+        TermArena {
+            nodes: vec![],
+            interned: HashMap::default(),
+        }
+    }
+}
+
+impl<T: IdentType> Index<TermId> for TermArena<T> {
+    type Output = Node<T>;
+
+    fn index(&self, id: TermId) -> &Self::Output {
+        &self.nodes[id.0]
+    }
+}
+
+impl<T: IdentType> TermArena<T> {
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn intern(&mut self, node: Node<T>) -> TermId {
+        if let Some(id) = self.interned.get(&node) {
+            return *id;
+        }
+        let id = TermId(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.interned.insert(node, id);
+        id
+    }
+
+    pub fn var(&mut self, x: T) -> TermId {
+        self.intern(Node::Var(x))
+    }
+
+    pub fn app(&mut self, e1: TermId, e2: TermId) -> TermId {
+        self.intern(Node::App(e1, e2))
+    }
+
+    pub fn abs(&mut self, x: T, ty: TermId, e: TermId) -> TermId {
+        self.intern(Node::Abs(x, ty, e))
+    }
+
+    pub fn pi(&mut self, x: T, lty: TermId, rty: TermId) -> TermId {
+        self.intern(Node::Pi(x, lty, rty))
+    }
+
+    pub fn kind(&mut self, kind: Kinds) -> TermId {
+        self.intern(Node::Kind(kind))
+    }
+
+    /// Intern a boxed term, sharing every subterm already present in the arena.
+    pub fn insert(&mut self, term: &Term<T>) -> TermId {
+        match term {
+            Var(x) => self.var(x.clone()),
+            App(e1, e2) => {
+                let (e1, e2) = (self.insert(e1), self.insert(e2));
+                self.app(e1, e2)
+            }
+            Abs(x, ty, e) => {
+                let (ty, e) = (self.insert(ty), self.insert(e));
+                self.abs(x.clone(), ty, e)
+            }
+            Pi(x, lty, rty) => {
+                let (lty, rty) = (self.insert(lty), self.insert(rty));
+                self.pi(x.clone(), lty, rty)
+            }
+            Kind(kind) => self.kind(*kind),
+        }
+    }
+
+    /// Rebuild the boxed term denoted by `id`.
+    pub fn extract(&self, id: TermId) -> Term<T> {
+        match &self[id] {
+            Node::Var(x) => Var(x.clone()),
+            Node::App(e1, e2) => App(box self.extract(*e1), box self.extract(*e2)),
+            Node::Abs(x, ty, e) => Abs(x.clone(), box self.extract(*ty), box self.extract(*e)),
+            Node::Pi(x, lty, rty) => Pi(x.clone(), box self.extract(*lty), box self.extract(*rty)),
+            Node::Kind(kind) => Kind(*kind),
+        }
+    }
+}
+
+impl TermArena<UID> {
+    /// Uids of the free variables of `id`.
+    pub fn fv(&self, id: TermId) -> HashSet<usize> {
+        match &self[id] {
+            Node::Var(x) => hashset! {x.uid},
+            Node::App(e1, e2) => &self.fv(*e1) | &self.fv(*e2),
+            Node::Abs(x, ty, e) | Node::Pi(x, ty, e) => {
+                let mut fv = self.fv(*e);
+                fv.remove(&x.uid);
+                &fv | &self.fv(*ty)
+            }
+            Node::Kind(_) => HashSet::default(),
+        }
+    }
+
+    /// Substitute `to` for every free occurrence of `from` in `id`, renaming the binders which
+    /// would capture a free variable of `to`.
+    ///
+    /// Subterms that don't mention `from` are returned as-is, so the result shares them with `id`.
+    pub fn subst(&mut self, id: TermId, from: &UID, to: TermId) -> TermId {
+        let to_fv = self.fv(to);
+        self._subst(id, from, to, &to_fv, &mut HashMap::default())
+    }
+
+    /// Substitute under the binder `x`, which is renamed first if it would capture `to`.
+    fn _subst_binder(
+        &mut self,
+        x: UID,
+        e: TermId,
+        from: &UID,
+        to: TermId,
+        to_fv: &HashSet<usize>,
+        memo: &mut HashMap<TermId, TermId>,
+    ) -> (UID, TermId) {
+        let e_ = self._subst(e, from, to, to_fv, memo);
+        if e_ == e || !to_fv.contains(&x.uid) {
+            return (x, e_);
+        }
+        let renamed = UID {
+            name: x.name.clone(),
+            uid: fresh_uid(),
+        };
+        let var = self.var(renamed.clone());
+        let e = self.subst(e, &x, var);
+        (renamed, self._subst(e, from, to, to_fv, memo))
+    }
+
+    fn _subst(
+        &mut self,
+        id: TermId,
+        from: &UID,
+        to: TermId,
+        to_fv: &HashSet<usize>,
+        memo: &mut HashMap<TermId, TermId>,
+    ) -> TermId {
+        if let Some(id) = memo.get(&id) {
+            return *id;
+        }
+        let rtn = match self[id].clone() {
+            Node::Var(x) => {
+                if x.uid == from.uid {
+                    to
+                } else {
+                    id
+                }
+            }
+            Node::App(e1, e2) => {
+                let (e1_, e2_) = (
+                    self._subst(e1, from, to, to_fv, memo),
+                    self._subst(e2, from, to, to_fv, memo),
+                );
+                if (e1_, e2_) == (e1, e2) {
+                    id
+                } else {
+                    self.app(e1_, e2_)
+                }
+            }
+            Node::Abs(x, ty, e) => {
+                let ty_ = self._subst(ty, from, to, to_fv, memo);
+                let (x_, e_) = self._subst_binder(x, e, from, to, to_fv, memo);
+                if (ty_, e_) == (ty, e) {
+                    id
+                } else {
+                    self.abs(x_, ty_, e_)
+                }
+            }
+            Node::Pi(x, lty, rty) => {
+                let lty_ = self._subst(lty, from, to, to_fv, memo);
+                let (x_, rty_) = self._subst_binder(x, rty, from, to, to_fv, memo);
+                if (lty_, rty_) == (lty, rty) {
+                    id
+                } else {
+                    self.pi(x_, lty_, rty_)
+                }
+            }
+            Node::Kind(_) => id,
+        };
+        memo.insert(id, rtn);
+        rtn
+    }
+
+    /// Contract the redex `(λx:τ.e) arg`, or return `None` if `id` isn't one.
+    pub fn contract(&mut self, id: TermId) -> Option<TermId> {
+        if let Node::App(e1, e2) = self[id] {
+            if let Node::Abs(x, _, e) = self[e1].clone() {
+                return Some(self.subst(e, &x, e2));
+            }
+        }
+        None
+    }
+}
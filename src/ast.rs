@@ -1,6 +1,12 @@
 use std::collections::HashSet;
 use std::fmt::{Debug, Display, Formatter, Result};
 use std::hash::Hash;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::pretty::{Syntax, DEFAULT_WIDTH};
 
 pub use Term::*;
 
@@ -11,6 +17,7 @@ pub trait Fresh {
 pub trait IdentType: Debug + Display + Clone + Eq + Hash {}
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ReduceStrategy {
     CBN,
     NOR,
@@ -21,6 +28,23 @@ pub enum ReduceStrategy {
     HNO,
 }
 
+impl FromStr for ReduceStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "CBN" => Ok(ReduceStrategy::CBN),
+            "NOR" => Ok(ReduceStrategy::NOR),
+            "CBV" => Ok(ReduceStrategy::CBV),
+            "APP" => Ok(ReduceStrategy::APP),
+            "HAP" => Ok(ReduceStrategy::HAP),
+            "HSR" => Ok(ReduceStrategy::HSR),
+            "HNO" => Ok(ReduceStrategy::HNO),
+            _ => Err(format!("unknown reduce strategy `{}`", s)),
+        }
+    }
+}
+
 pub trait Reducible: Sized {
     fn subst(&self, ex: &Self) -> Self;
     fn beta_reduce(&self, strategy: ReduceStrategy, limit: Option<usize>) -> Self;
@@ -39,9 +63,14 @@ pub trait Reducible: Sized {
     fn equals(&self, other: &Self) -> bool;
 }
 
-type Ty<T> = Term<T>;
+pub type Ty<T> = Term<T>;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Kinds {
     Star,
     Box,
@@ -53,7 +82,12 @@ impl Display for Kinds {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Term<T: IdentType> {
     Var(T),
     App(Box<Term<T>>, Box<Term<T>>),
@@ -62,6 +96,26 @@ pub enum Term<T: IdentType> {
     Kind(Kinds),
 }
 
+/// The terms visited by a reduction one step apart, starting from the reduced term.
+#[derive(Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Trace<T: IdentType> {
+    pub strategy: ReduceStrategy,
+    pub steps: Vec<Term<T>>,
+    /// Whether the last term can't be reduced any further, rather than the limit running out.
+    pub normal: bool,
+}
+
+/// Top-level declaration of a source file.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Decl<T: IdentType> {
+    Def(T, Option<Ty<T>>, Term<T>),
+    Axiom(T, Ty<T>),
+    Eval(Term<T>, Option<ReduceStrategy>),
+    Check(Term<T>),
+    Assert(Term<T>, Term<T>),
+}
+
 pub fn abs<T: IdentType>(bound: T, ty: Ty<T>, term: Term<T>) -> Term<T> {
     Abs(bound, box ty, box term)
 }
@@ -90,69 +144,11 @@ impl<T: IdentType> Term<T> {
             Kind(_) => HashSet::new(),
         }
     }
-
-    fn shows_prec(&self, prec: usize, debug: bool) -> String {
-        match self {
-            Term::Var(chr) => format!("{}", chr),
-            Term::App(lhs, rhs) => {
-                let rtn = format!("{} {}", lhs.shows_prec(1, debug), rhs.shows_prec(2, debug));
-                if prec > 1 {
-                    if debug {
-                        format!("({:?})", rtn)
-                    } else {
-                        format!("({})", rtn)
-                    }
-                } else {
-                    rtn
-                }
-            }
-            Term::Abs(bound, ty, term) => {
-                let rtn = format!(
-                    "λ{}:{}.{}",
-                    bound,
-                    ty.shows_prec(0, debug),
-                    term.shows_prec(0, debug)
-                );
-                if prec > 0 {
-                    if debug {
-                        format!("({:?})", rtn)
-                    } else {
-                        format!("({})", rtn)
-                    }
-                } else {
-                    rtn
-                }
-            }
-            Term::Pi(bound, lty, rty) => {
-                let rtn = format!(
-                    "π{}:{}.{}",
-                    bound,
-                    lty.shows_prec(0, debug),
-                    rty.shows_prec(0, debug)
-                );
-                if prec > 0 {
-                    if debug {
-                        format!("({:?})", rtn)
-                    } else {
-                        format!("({})", rtn)
-                    }
-                } else {
-                    rtn
-                }
-            }
-            Term::Kind(kinds) => kinds.to_string(),
-        }
-    }
 }
 
 impl<T: IdentType> Display for Term<T> {
+    /// Lines are broken at the formatter's width, e.g. `{:40}`, or at `DEFAULT_WIDTH`.
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}", self.shows_prec(0, false))
+        self.write_pretty(Syntax::Unicode, f.width().unwrap_or(DEFAULT_WIDTH), f)
     }
 }
-
-// impl<T: IdentType> Debug for Term<T> {
-//     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-//         write!(f, "{}", self.shows_prec(0, true))
-//     }
-// }
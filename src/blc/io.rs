@@ -0,0 +1,97 @@
+//! The byte oriented I/O convention of BLC: a program is applied to its input, a list of bytes,
+//! and must normalize to its output, another list of bytes. Lists are built from pairs
+//! `λz.z head tail` and end with `λx y.y`, bytes are lists of 8 bits, most significant first,
+//! and the bits 0 and 1 are `λx y.x` and `λx y.y`.
+
+use crate::ast::*;
+use crate::blc::BlcError;
+use crate::index::bare::BareIdent;
+use crate::index::dbi::DBI;
+use crate::index::uid::{DEFAULT_LIMIT, UID};
+
+fn star() -> Ty<BareIdent> {
+    Kind(Kinds::Star)
+}
+
+/// `λx y.x` if `first`, else `λx y.y`.
+fn select(first: bool) -> Term<BareIdent> {
+    let (x, y) = ("x".to_string(), "y".to_string());
+    let body = Var(if first { x.clone() } else { y.clone() });
+    abs(x, star(), abs(y, star(), body))
+}
+
+fn list(elems: impl DoubleEndedIterator<Item = Term<BareIdent>>) -> Term<BareIdent> {
+    elems.rev().fold(select(false), |tail, head| {
+        let z = "z".to_string();
+        abs(z.clone(), star(), app(app(Var(z), head), tail))
+    })
+}
+
+/// The input list holding `bytes`.
+pub fn input(bytes: &[u8]) -> Term<UID> {
+    let byte = |byte: u8| list((0..8).rev().map(move |i| select(byte >> i & 1 == 0)));
+    Term::from(list(bytes.iter().map(|b| byte(*b))))
+}
+
+/// The elements of a list in De Bruijn form.
+fn elems(mut term: &Term<DBI<UID>>) -> Option<Vec<&Term<DBI<UID>>>> {
+    let mut elems = vec![];
+    loop {
+        match term {
+            Abs(_, _, e) => match &**e {
+                Abs(_, _, e) if matches!(**e, Var(DBI::Bound(0, _))) => return Some(elems),
+                App(pair, tail) => match &**pair {
+                    App(z, head) if matches!(**z, Var(DBI::Bound(0, _))) => {
+                        elems.push(&**head);
+                        term = tail;
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+}
+
+fn bit(term: &Term<DBI<UID>>) -> Option<bool> {
+    match term {
+        Abs(_, _, e) => match &**e {
+            Abs(_, _, e) => match **e {
+                Var(DBI::Bound(1, _)) => Some(false),
+                Var(DBI::Bound(0, _)) => Some(true),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn byte(term: &Term<DBI<UID>>) -> Option<u8> {
+    match elems(term)?.as_slice() {
+        bits @ [_, _, _, _, _, _, _, _] => bits
+            .iter()
+            .try_fold(0, |byte, b| Some(byte << 1 | bit(b)? as u8)),
+        _ => None,
+    }
+}
+
+/// The bytes of an output list in normal form.
+pub fn output(term: &Term<UID>) -> Result<Vec<u8>, BlcError> {
+    let dbi = term.to_dbi();
+    elems(&dbi)
+        .and_then(|bytes| bytes.into_iter().map(byte).collect())
+        .ok_or_else(|| BlcError::Output(Term::from(term.clone())))
+}
+
+/// Apply `program` to `input` and normalize in normal order, within `limit` steps or
+/// `DEFAULT_LIMIT` by default.
+pub fn run(program: &Term<UID>, bytes: &[u8], limit: Option<usize>) -> Result<Vec<u8>, BlcError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let result = app(program.clone(), input(bytes)).beta_reduce(ReduceStrategy::NOR, Some(limit));
+    if result.beta_reduce(ReduceStrategy::NOR, Some(1)) != result {
+        return Err(BlcError::Limit(limit));
+    }
+    output(&result)
+}
@@ -0,0 +1,164 @@
+//! John Tromp's Binary Lambda Calculus, where `λM` is written `00M`, `M N` is written `01MN`,
+//! and the variable of De Bruijn index `i`, counting from 1, is written `1ⁱ0`.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::*;
+use crate::index::bare::BareIdent;
+use crate::index::dbi::DBI;
+use crate::index::uid::{fresh_uid, UID};
+
+pub use io::{input, output, run};
+
+mod io;
+
+#[derive(Clone, Debug)]
+pub enum BlcError {
+    /// Only closed terms have an encoding.
+    Free(BareIdent),
+    /// A product or a sort outside of a binder domain.
+    Typed(Term<BareIdent>),
+    /// A character other than `0`, `1` or whitespace in a bit string.
+    Digit(char),
+    /// The bits end in the middle of a term.
+    Truncated,
+    /// Bits left over after the term, and how many.
+    Trailing(usize),
+    /// A variable whose index exceeds the number of enclosing abstractions.
+    Unbound(usize),
+    /// A normal form which isn't a list of bytes.
+    Output(Term<BareIdent>),
+    /// The program didn't reach a normal form within that many steps.
+    Limit(usize),
+}
+
+impl Display for BlcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BlcError::Free(x) => {
+                write!(f, "`{}` is free, only closed terms have a BLC encoding", x)
+            }
+            BlcError::Typed(term) => {
+                write!(f, "`{}` is a type, BLC only encodes untyped terms", term)
+            }
+            BlcError::Digit(c) => write!(f, "`{}` is not a bit", c),
+            BlcError::Truncated => write!(f, "the bits end in the middle of a term"),
+            BlcError::Trailing(n) => write!(f, "{} bits left over after the term", n),
+            BlcError::Unbound(i) => write!(f, "variable {} is out of scope", i),
+            BlcError::Output(term) => write!(f, "`{}` is not a list of bytes", term),
+            BlcError::Limit(limit) => write!(f, "no normal form within {} steps", limit),
+        }
+    }
+}
+
+/// Read a bit string, ignoring whitespace.
+pub fn parse_bits(s: &str) -> Result<Vec<bool>, BlcError> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            c => Err(BlcError::Digit(c)),
+        })
+        .collect()
+}
+
+pub fn show_bits(bits: &[bool]) -> String {
+    bits.iter()
+        .map(|bit| if *bit { '1' } else { '0' })
+        .collect()
+}
+
+fn encode(term: &Term<DBI<UID>>, bits: &mut Vec<bool>) -> Result<(), BlcError> {
+    match term {
+        Var(DBI::Bound(i, _)) => {
+            bits.extend((0..=*i).map(|_| true));
+            bits.push(false);
+        }
+        Var(DBI::Free(x)) | Var(DBI::Binder(x)) => return Err(BlcError::Free(x.name.clone())),
+        Abs(_, _, e) => {
+            bits.extend([false, false]);
+            encode(e, bits)?;
+        }
+        App(e1, e2) => {
+            bits.extend([false, true]);
+            encode(e1, bits)?;
+            encode(e2, bits)?;
+        }
+        Pi(_, _, _) | Kind(_) => unreachable!("Types are rejected by `untyped`."),
+    }
+    Ok(())
+}
+
+/// Whether the term only has products and sorts in binder domains.
+fn untyped(term: &Term<UID>) -> Result<(), BlcError> {
+    match term {
+        Var(_) => Ok(()),
+        Abs(_, _, e) => untyped(e),
+        App(e1, e2) => untyped(e1).and_then(|_| untyped(e2)),
+        Pi(_, _, _) | Kind(_) => Err(BlcError::Typed(Term::from(term.clone()))),
+    }
+}
+
+/// Decode a term from the front of `bits` under the binders of `scope`, innermost last.
+fn decode(
+    bits: &mut impl Iterator<Item = bool>,
+    scope: &mut Vec<UID>,
+) -> Result<Term<UID>, BlcError> {
+    match (bits.next(), bits.next()) {
+        (Some(false), Some(false)) => {
+            let x = UID {
+                name: "x".to_string(),
+                uid: fresh_uid(),
+            };
+            scope.push(x.clone());
+            let body = decode(bits, scope);
+            scope.pop();
+            Ok(Abs(x, box Kind(Kinds::Star), box body?))
+        }
+        (Some(false), Some(true)) => {
+            let e1 = decode(bits, scope)?;
+            let e2 = decode(bits, scope)?;
+            Ok(App(box e1, box e2))
+        }
+        (Some(true), second) => {
+            let mut index = 1;
+            let mut bit = second;
+            while let Some(true) = bit {
+                index += 1;
+                bit = bits.next();
+            }
+            match (bit, scope.len().checked_sub(index)) {
+                (None, _) => Err(BlcError::Truncated),
+                (_, None) => Err(BlcError::Unbound(index)),
+                (_, Some(i)) => Ok(Var(scope[i].clone())),
+            }
+        }
+        _ => Err(BlcError::Truncated),
+    }
+}
+
+impl Term<UID> {
+    /// The BLC encoding of a closed term. Binder domains are erased.
+    pub fn to_blc(&self) -> Result<Vec<bool>, BlcError> {
+        untyped(self)?;
+        let mut bits = vec![];
+        encode(&self.to_dbi(), &mut bits)?;
+        Ok(bits)
+    }
+
+    /// Tromp's complexity measure, the length of the encoding.
+    pub fn size_in_bits(&self) -> Result<usize, BlcError> {
+        Ok(self.to_blc()?.len())
+    }
+
+    /// Decode a whole bit string. Binders are named `x` and given `*` as domain.
+    pub fn from_blc(bits: &[bool]) -> Result<Term<UID>, BlcError> {
+        let mut bits = bits.iter().cloned();
+        let term = decode(&mut bits, &mut vec![])?;
+        match bits.count() {
+            0 => Ok(term),
+            n => Err(BlcError::Trailing(n)),
+        }
+    }
+}
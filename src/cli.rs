@@ -0,0 +1,516 @@
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::{fs, mem};
+
+#[cfg(feature = "serde")]
+use serde_json::{json, Value};
+
+use crate::ast::*;
+use crate::blc::{parse_bits, run as run_blc, show_bits, BlcError};
+use crate::codegen::CodegenError;
+use crate::combinators::{Basis, Bracket, Comb, CombError};
+use crate::encoding::Encoding;
+use crate::env::{Env, EnvError};
+use crate::export::tromp::Diagram;
+use crate::format::format_module;
+use crate::index::bare::BareIdent;
+use crate::index::uid::UID;
+use crate::lazyk::{LazyK, LazyKError};
+use crate::pretty::Syntax;
+use crate::vm::{Mode, VmError};
+
+const USAGE: &str = "\
+usage: lq                         start a REPL
+       lq eval <term> [--strategy <strategy>] [--limit <steps>] [--trace]
+                      [--prelude untyped|system-f] [--diagram text|svg]
+                      [--vm strict|lazy]  normalize on the bytecode machine instead
+       lq check <file> [--limit <steps>]
+                                  process every declaration of a file
+       lq fmt <file> [--width <columns>]
+       lq convert [<term>] --to dbi|uid|bare|ascii|unicode|latex|sexpr|json|dot|svg
+                      |diagram|tromp|ski|bckw|blc|c|js|scheme
+                      [--bracket naive|eta|turner]  bracket abstraction for ski and bckw
+                      [--prelude untyped|system-f]  definitions for c, js and scheme
+                      [--thunks]  call-by-name js and scheme
+                      [--redex <n>]  highlight the n-th redex, leftmost outermost first
+       lq run <term> [--limit <steps>] [--encoding church|scott]
+                      [--prelude untyped|system-f]
+                                  run the term as a Lazy K style program from stdin
+                                  to stdout, bytes being numerals of the encoding
+       lq blc <bits> [--limit <steps>]
+                                  run a binary lambda calculus program on the bytes
+                                  of stdin, and write the bytes it outputs to stdout
+
+Terms are read from stdin when omitted, and so are files given as `-`.
+With --json, results and errors are printed as a JSON object instead.
+
+exit status: 0 ok, 1 step limit hit before a normal form, 2 parse error,
+             3 type error or failed assertion, 4 usage or I/O error";
+
+/// Outcome of a command, which is also its exit code.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Status {
+    Ok = 0,
+    Limit = 1,
+    ParseError = 2,
+    Error = 3,
+    Usage = 4,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Status::Ok => "ok",
+            Status::Limit => "limit",
+            Status::ParseError => "parse_error",
+            Status::Error => "error",
+            Status::Usage => "usage",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    Env(EnvError),
+    Combinators(CombError),
+    Blc(BlcError),
+    LazyK(LazyKError),
+    Vm(VmError),
+    Codegen(CodegenError),
+    Io(String, io::Error),
+    Usage(String),
+}
+
+impl CliError {
+    fn status(&self) -> Status {
+        match self {
+            CliError::Env(EnvError::Parse(_))
+            | CliError::Blc(BlcError::Digit(_))
+            | CliError::Blc(BlcError::Truncated)
+            | CliError::Blc(BlcError::Trailing(_))
+            | CliError::Blc(BlcError::Unbound(_)) => Status::ParseError,
+            CliError::Blc(BlcError::Limit(_))
+            | CliError::LazyK(LazyKError::Limit(_))
+            | CliError::Vm(VmError::Limit(_)) => Status::Limit,
+            CliError::Env(_)
+            | CliError::Combinators(_)
+            | CliError::Blc(_)
+            | CliError::LazyK(_)
+            | CliError::Vm(_)
+            | CliError::Codegen(_) => Status::Error,
+            CliError::Io(_, _) | CliError::Usage(_) => Status::Usage,
+        }
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Env(e) => write!(f, "{}", e),
+            CliError::Combinators(e) => write!(f, "{}", e),
+            CliError::Blc(e) => write!(f, "{}", e),
+            CliError::LazyK(e) => write!(f, "{}", e),
+            CliError::Vm(e) => write!(f, "{}", e),
+            CliError::Codegen(e) => write!(f, "{}", e),
+            CliError::Io(path, e) => write!(f, "can't read `{}`: {}", path, e),
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+        }
+    }
+}
+
+impl<E: Into<EnvError>> From<E> for CliError {
+    fn from(e: E) -> Self {
+        CliError::Env(e.into())
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    positional: Vec<String>,
+    strategy: Option<ReduceStrategy>,
+    limit: Option<usize>,
+    trace: bool,
+    json: bool,
+    to: Option<String>,
+    width: Option<usize>,
+    prelude: Option<String>,
+    redex: Option<usize>,
+    diagram: Option<String>,
+    bracket: Option<Bracket>,
+    encoding: Option<Encoding>,
+    vm: Option<Mode>,
+    thunks: bool,
+}
+
+fn usage(message: String) -> CliError {
+    CliError::Usage(message)
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, CliError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| usage(format!("`{}` needs a value", arg)))
+            };
+            match arg.as_str() {
+                "--strategy" => parsed.strategy = Some(value()?.parse().map_err(usage)?),
+                "--limit" => parsed.limit = Some(number(&value()?)?),
+                "--width" => parsed.width = Some(number(&value()?)?),
+                "--to" => parsed.to = Some(value()?),
+                "--prelude" => parsed.prelude = Some(value()?),
+                "--bracket" => parsed.bracket = Some(value()?.parse().map_err(usage)?),
+                "--encoding" => parsed.encoding = Some(value()?.parse().map_err(usage)?),
+                "--vm" => parsed.vm = Some(value()?.parse().map_err(usage)?),
+                "--diagram" => parsed.diagram = Some(value()?),
+                "--redex" => parsed.redex = Some(number(&value()?)?),
+                "--trace" => parsed.trace = true,
+                "--json" => parsed.json = true,
+                "--thunks" => parsed.thunks = true,
+                _ if arg.starts_with("--") => {
+                    return Err(usage(format!("unknown option `{}`", arg)))
+                }
+                _ => parsed.positional.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// The only positional argument, if any.
+    fn input(&mut self) -> Result<Option<String>, CliError> {
+        match mem::take(&mut self.positional).as_slice() {
+            [] => Ok(None),
+            [input] => Ok(Some(input.clone())),
+            [_, extra, ..] => Err(usage(format!("unexpected argument `{}`", extra))),
+        }
+    }
+}
+
+fn number(arg: &str) -> Result<usize, CliError> {
+    arg.parse()
+        .map_err(|_| usage(format!("`{}` is not a number", arg)))
+}
+
+fn stdin() -> Result<String, CliError> {
+    let mut source = String::new();
+    io::stdin()
+        .read_to_string(&mut source)
+        .map_err(|e| CliError::Io("<stdin>".to_string(), e))?;
+    Ok(source)
+}
+
+fn read_file(path: Option<String>) -> Result<String, CliError> {
+    match path.as_deref() {
+        None => Err(usage("missing file".to_string())),
+        Some("-") => stdin(),
+        Some(path) => fs::read_to_string(path).map_err(|e| CliError::Io(path.to_string(), e)),
+    }
+}
+
+/// What a command printed, as text and as JSON.
+struct Report {
+    status: Status,
+    text: String,
+    #[cfg(feature = "serde")]
+    json: Value,
+}
+
+#[cfg(feature = "serde")]
+fn term_json(term: &Term<BareIdent>) -> Value {
+    serde_json::to_value(term).unwrap()
+}
+
+fn prelude(args: &Args) -> Result<Env, CliError> {
+    let mut env = match args.prelude.as_deref() {
+        None => Env::default(),
+        Some("untyped") => Env::untyped_prelude(),
+        Some("system-f") => Env::system_f_prelude(),
+        Some(prelude) => return Err(usage(format!("unknown prelude `{}`", prelude))),
+    };
+    env.limit = args.limit;
+    Ok(env)
+}
+
+fn eval(mut args: Args) -> Result<Report, CliError> {
+    let source = args.input()?.map_or_else(stdin, Ok)?;
+    let env = prelude(&args)?;
+    let term = env.elaborate(&env.parse(&source)?);
+    if let Some(mode) = args.vm {
+        return eval_vm(&term, mode, args.limit);
+    }
+    let trace = Trace::<BareIdent>::from(
+        term.trace(args.strategy.unwrap_or(ReduceStrategy::NOR), args.limit),
+    );
+    let result = trace.steps.last().unwrap();
+    let value = Encoding::default().read(result);
+
+    let mut lines = vec![];
+    match args.diagram.as_deref() {
+        None => {
+            if args.trace {
+                lines.extend(trace.steps[1..].iter().map(|step| format!("--> {}", step)));
+            }
+            lines.push(value.to_string());
+        }
+        Some("text") if args.trace => {
+            let frames = trace
+                .diagrams()
+                .iter()
+                .map(Diagram::to_text)
+                .collect::<Vec<_>>();
+            lines.push(frames.join("\n").trim_end().to_string());
+        }
+        Some("text") => lines.push(Diagram::new(result).to_text().trim_end().to_string()),
+        Some("svg") if args.trace => lines.push(trace.to_animated_svg().trim_end().to_string()),
+        Some("svg") => lines.push(Diagram::new(result).to_svg().trim_end().to_string()),
+        Some(diagram) => return Err(usage(format!("unknown diagram `{}`", diagram))),
+    }
+    if !trace.normal {
+        lines.push(format!("stopped after {} steps", trace.steps.len() - 1));
+    }
+    Ok(Report {
+        status: if trace.normal {
+            Status::Ok
+        } else {
+            Status::Limit
+        },
+        text: lines.join("\n"),
+        #[cfg(feature = "serde")]
+        json: json!({
+            "status": if trace.normal { Status::Ok } else { Status::Limit }.to_string(),
+            "normal": trace.normal,
+            "steps": trace.steps.len() - 1,
+            "result": term_json(result),
+            "printed": result.to_string(),
+            "value": value.to_string(),
+            "trace": if args.trace {
+                Value::Array(trace.steps.iter().map(term_json).collect())
+            } else {
+                Value::Null
+            },
+        }),
+    })
+}
+
+fn eval_vm(term: &Term<UID>, mode: Mode, limit: Option<usize>) -> Result<Report, CliError> {
+    let result = Term::<BareIdent>::from(term.vm_nf(mode, limit).map_err(CliError::Vm)?);
+    let value = Encoding::default().read(&result);
+    Ok(Report {
+        status: Status::Ok,
+        text: value.to_string(),
+        #[cfg(feature = "serde")]
+        json: json!({
+            "status": Status::Ok.to_string(),
+            "normal": true,
+            "result": term_json(&result),
+            "printed": result.to_string(),
+            "value": value.to_string(),
+        }),
+    })
+}
+
+fn check(mut args: Args) -> Result<Report, CliError> {
+    let source = read_file(args.input()?)?;
+    let mut env = Env::default();
+    env.limit = args.limit;
+    let responses: Vec<_> = env.load(&source)?.iter().map(ToString::to_string).collect();
+    Ok(Report {
+        status: Status::Ok,
+        text: responses.join("\n"),
+        #[cfg(feature = "serde")]
+        json: json!({ "status": Status::Ok.to_string(), "responses": responses }),
+    })
+}
+
+/// Print every declaration of a file in a canonical layout, keeping comments, blank lines and
+/// literals as written.
+fn fmt(mut args: Args) -> Result<Report, CliError> {
+    let source = read_file(args.input()?)?;
+    let width = args.width.unwrap_or(crate::pretty::DEFAULT_WIDTH);
+    let text = format_module(&source, width)?;
+    Ok(Report {
+        status: Status::Ok,
+        #[cfg(feature = "serde")]
+        json: json!({ "status": Status::Ok.to_string(), "output": text }),
+        text,
+    })
+}
+
+fn convert(mut args: Args) -> Result<Report, CliError> {
+    let source = args.input()?.map_or_else(stdin, Ok)?;
+    let env = prelude(&args)?;
+    let term = env.parse(&source)?;
+    let width = args.width.unwrap_or(crate::pretty::DEFAULT_WIDTH);
+    let syntax = |syntax| format!("{:w$}", term.display(syntax), w = width);
+    let uid = Term::<UID>::from(term.clone());
+    let redex = match args.redex {
+        Some(n) => match uid.redexes().into_iter().nth(n) {
+            Some(path) => Some(path),
+            None => return Err(usage(format!("the term has no redex #{}", n))),
+        },
+        None => None,
+    };
+    let text = match args.to.as_deref() {
+        Some("dbi") => term.to_dbi().to_string(),
+        Some("uid") => uid.to_string(),
+        Some("bare") | Some("unicode") => syntax(Syntax::Unicode),
+        Some("ascii") => syntax(Syntax::Ascii),
+        Some("latex") => syntax(Syntax::Latex),
+        Some("sexpr") => syntax(Syntax::SExpr),
+        #[cfg(feature = "serde")]
+        Some("json") => term_json(&term).to_string(),
+        Some("dot") => uid.to_dot(redex.as_deref()).trim_end().to_string(),
+        Some("svg") => uid.to_svg(redex.as_deref()).trim_end().to_string(),
+        Some("diagram") => Diagram::new(&term).to_text().trim_end().to_string(),
+        Some("tromp") => Diagram::new(&term).to_svg().trim_end().to_string(),
+        Some("blc") => show_bits(&uid.to_blc().map_err(CliError::Blc)?),
+        Some("c") => env
+            .elaborate(&term)
+            .to_c()
+            .map_err(CliError::Codegen)?
+            .trim_end()
+            .to_string(),
+        Some("js") => Term::<BareIdent>::from(env.elaborate(&term)).to_js(args.thunks, width),
+        Some("scheme") => {
+            Term::<BareIdent>::from(env.elaborate(&term)).to_scheme(args.thunks, width)
+        }
+        Some(to @ "ski") | Some(to @ "bckw") => {
+            let basis = if to == "ski" { Basis::Ski } else { Basis::Bckw };
+            Comb::from_term(&term, basis, args.bracket.unwrap_or_default())
+                .map_err(CliError::Combinators)?
+                .to_string()
+        }
+        Some(to) => return Err(usage(format!("can't convert to `{}`", to))),
+        None => return Err(usage("missing `--to`".to_string())),
+    };
+    Ok(Report {
+        status: Status::Ok,
+        #[cfg(feature = "serde")]
+        json: json!({ "status": Status::Ok.to_string(), "output": text }),
+        text,
+    })
+}
+
+/// Write the output as it is produced, so interactive programs can respond to each line.
+fn run_lazy_k(mut args: Args) -> Result<Report, CliError> {
+    let source = args
+        .input()?
+        .ok_or_else(|| usage("missing program".to_string()))?;
+    let env = prelude(&args)?;
+    let program = env.elaborate(&env.parse(&source)?);
+    let input = io::stdin().bytes().map_while(Result::ok);
+    let encoding = args.encoding.unwrap_or_default();
+    let mut output = vec![];
+    for byte in LazyK::new(&program, input, encoding, args.limit) {
+        let byte = byte.map_err(CliError::LazyK)?;
+        output.push(byte);
+        if !args.json && !write_stdout(&[byte])? {
+            break;
+        }
+    }
+    Ok(Report {
+        status: Status::Ok,
+        text: String::new(),
+        #[cfg(feature = "serde")]
+        json: json!({
+            "status": Status::Ok.to_string(),
+            "output": String::from_utf8_lossy(&output),
+            "bytes": output,
+        }),
+    })
+}
+
+fn blc(mut args: Args) -> Result<Report, CliError> {
+    let source = args
+        .input()?
+        .ok_or_else(|| usage("missing program".to_string()))?;
+    let program = Term::<UID>::from_blc(&parse_bits(&source).map_err(CliError::Blc)?)
+        .map_err(CliError::Blc)?;
+    let mut input = vec![];
+    io::stdin()
+        .read_to_end(&mut input)
+        .map_err(|e| CliError::Io("<stdin>".to_string(), e))?;
+    let output = run_blc(&program, &input, args.limit).map_err(CliError::Blc)?;
+    if !args.json {
+        write_stdout(&output)?;
+    }
+    Ok(Report {
+        status: Status::Ok,
+        text: String::new(),
+        #[cfg(feature = "serde")]
+        json: json!({
+            "status": Status::Ok.to_string(),
+            "output": String::from_utf8_lossy(&output),
+            "bytes": output,
+        }),
+    })
+}
+
+/// Run a batch command, given the arguments after the program name, and return its exit code.
+/// Write to stdout, returning whether it's still open. A closed pipe, as with `| head`, ends
+/// the output early rather than being an error.
+fn write_stdout(bytes: &[u8]) -> Result<bool, CliError> {
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(bytes).and_then(|_| stdout.flush()) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(e) => Err(CliError::Io("<stdout>".to_string(), e)),
+    }
+}
+
+/// Print `text` on its own line and exit with `status`, unless stdout fails.
+fn print_line(text: impl Display, status: i32) -> i32 {
+    match write_stdout(format!("{}\n", text).as_bytes()) {
+        Ok(_) => status,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            e.status() as i32
+        }
+    }
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut args = args.into_iter();
+    let command = args.next().unwrap_or_default();
+    if matches!(command.as_str(), "help" | "--help" | "-h") {
+        return print_line(USAGE, Status::Ok as i32);
+    }
+    let args = Args::parse(args);
+    let json = args.as_ref().map_or(false, |args| args.json);
+    let report = args.and_then(|args| match command.as_str() {
+        "eval" => eval(args),
+        "check" => check(args),
+        "fmt" => fmt(args),
+        "convert" => convert(args),
+        "blc" => blc(args),
+        "run" => run_lazy_k(args),
+        _ => Err(usage(format!("unknown command `{}`", command))),
+    });
+    match (report, json) {
+        #[cfg(feature = "serde")]
+        (Ok(report), true) => print_line(report.json, report.status as i32),
+        #[cfg(feature = "serde")]
+        (Err(e), true) => {
+            let error = match &e {
+                CliError::Usage(message) => message.clone(),
+                e => e.to_string(),
+            };
+            let json = json!({ "status": e.status().to_string(), "error": error });
+            print_line(json, e.status() as i32)
+        }
+        #[cfg(not(feature = "serde"))]
+        (_, true) => {
+            eprintln!("error: --json needs the `serde` feature");
+            Status::Usage as i32
+        }
+        (Ok(report), false) if report.text.is_empty() => report.status as i32,
+        (Ok(report), false) => print_line(report.text, report.status as i32),
+        (Err(e), false) => {
+            eprintln!("error: {}", e);
+            e.status() as i32
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! A C backend, evaluating by value like `CBV`, on a runtime of closures allocated by bumping a
+//! pointer and never freed. The program prints its result as a Church numeral, which it reads
+//! back by applying it to a built-in successor and zero, under a type argument if needed.
+
+use std::fmt::Write;
+
+use crate::ast::*;
+use crate::codegen::{CodegenError, Expr, Function, Program};
+use crate::index::uid::UID;
+
+const RUNTIME: &str = r#"#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+typedef struct value value;
+typedef value *(*code)(value *self, value *arg);
+
+enum tag { CLOSURE, NAT, SUCC, TYPE, STUCK };
+
+struct value {
+    enum tag tag;
+    unsigned long nat;
+    code fn;
+    value *env[];
+};
+
+/* Types are all the same value, and so are applications which can't go any further. */
+static value succ = {SUCC}, type = {TYPE}, stuck = {STUCK};
+
+#define CHUNK (1 << 20)
+
+static char *heap;
+static size_t left;
+
+static void *alloc(size_t size) {
+    size = (size + 15) & ~(size_t)15;
+    if (left < size) {
+        left = size > CHUNK ? size : CHUNK;
+        heap = malloc(left);
+        if (!heap) {
+            fputs("out of memory\n", stderr);
+            exit(2);
+        }
+    }
+    void *p = heap;
+    heap += size;
+    left -= size;
+    return p;
+}
+
+static value *closure(code fn, size_t n, ...) {
+    value *v = alloc(sizeof(value) + n * sizeof(value *));
+    va_list captures;
+    va_start(captures, n);
+    v->tag = CLOSURE;
+    v->fn = fn;
+    for (size_t i = 0; i < n; i++)
+        v->env[i] = va_arg(captures, value *);
+    va_end(captures);
+    return v;
+}
+
+static value *nat(unsigned long n) {
+    value *v = alloc(sizeof(value));
+    v->tag = NAT;
+    v->nat = n;
+    return v;
+}
+
+static value *apply(value *f, value *arg) {
+    switch (f->tag) {
+    case CLOSURE:
+        return f->fn(f, arg);
+    case SUCC:
+        return arg->tag == NAT ? nat(arg->nat + 1) : &stuck;
+    default:
+        return &stuck;
+    }
+}
+
+static int read_nat(value *v) {
+    value *n = apply(apply(v, &succ), nat(0));
+    if (n->tag != NAT)
+        n = apply(apply(apply(v, &type), &succ), nat(0));
+    if (n->tag != NAT) {
+        fputs("the result is not a numeral\n", stderr);
+        return 1;
+    }
+    printf("%lu\n", n->nat);
+    return 0;
+}
+"#;
+
+/// A C identifier for the variable, made unique by its uid.
+fn ident(x: &UID) -> String {
+    let name: String = x
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => format!("{}_{}", name, x.uid),
+        _ => format!("v{}_{}", name, x.uid),
+    }
+}
+
+fn expr(e: &Expr) -> String {
+    match e {
+        Expr::Var(x) => ident(x),
+        Expr::App(e1, e2) => format!("apply({}, {})", expr(e1), expr(e2)),
+        Expr::Closure(i, captures) => {
+            let mut args = format!("lambda{}, {}", i, captures.len());
+            for x in captures {
+                write!(args, ", {}", ident(x)).unwrap();
+            }
+            format!("closure({})", args)
+        }
+        Expr::Type => "&type".to_string(),
+    }
+}
+
+fn function(i: usize, f: &Function, out: &mut String) {
+    write!(out, "\n/* λ{}", f.param.name).unwrap();
+    if !f.captures.is_empty() {
+        let names: Vec<_> = f.captures.iter().map(|x| x.name.as_str()).collect();
+        write!(out, ", capturing {}", names.join(" ")).unwrap();
+    }
+    writeln!(out, " */").unwrap();
+    writeln!(
+        out,
+        "static value *lambda{}(value *self, value *{}) {{",
+        i,
+        ident(&f.param)
+    )
+    .unwrap();
+    for (j, x) in f.captures.iter().enumerate() {
+        writeln!(out, "    value *{} = self->env[{}];", ident(x), j).unwrap();
+    }
+    writeln!(out, "    return {};", expr(&f.body)).unwrap();
+    writeln!(out, "}}").unwrap();
+}
+
+impl Program {
+    /// A standalone C program printing the numeral the term evaluates to.
+    pub fn to_c(&self) -> String {
+        let mut out = RUNTIME.to_string();
+        for (i, f) in self.functions.iter().enumerate() {
+            function(i, f, &mut out);
+        }
+        writeln!(out, "\nint main(void) {{").unwrap();
+        writeln!(out, "    return read_nat({});", expr(&self.main)).unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+impl Term<UID> {
+    pub fn to_c(&self) -> Result<String, CodegenError> {
+        Ok(self.closure_convert()?.to_c())
+    }
+}
@@ -0,0 +1,158 @@
+//! JavaScript arrow functions, curried. A Church numeral `n` is read back with
+//! `n(k => k + 1)(0)`, after a first argument for its type in System F.
+//!
+//! With thunks, arguments are passed as `() => arg` and variables are called to get their
+//! value, which gives call-by-name. Decoders then take thunks as well:
+//! `n(() => k => k() + 1)(() => 0)`.
+
+use std::fmt::Write;
+
+use crate::ast::*;
+use crate::codegen::{subscript, Scope};
+use crate::index::bare::BareIdent;
+use crate::pretty::parens;
+use crate::pretty::Doc::{self, *};
+
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Primes become `$`, subscripts digits, and other symbols their code point after a `$`.
+/// Reserved words get a trailing `$`.
+fn mangle(name: &str) -> String {
+    let mut ident = String::new();
+    for c in name.chars() {
+        match c {
+            '\'' | '′' => ident.push('$'),
+            c if c == '_' || c.is_ascii_alphanumeric() || c.is_alphabetic() => ident.push(c),
+            c => match subscript(c) {
+                Some(digit) => ident.push(digit),
+                None => write!(ident, "${:x}", c as u32).unwrap(),
+            },
+        }
+    }
+    if RESERVED.contains(&ident.as_str()) {
+        ident.push('$');
+    }
+    ident
+}
+
+/// The layout of the term, `prec` being 1 for the callee of a call and 0 elsewhere, as arrow
+/// functions bind looser than calls and arguments are already enclosed in parentheses.
+fn to_doc<'a>(term: &'a Term<BareIdent>, scope: &mut Scope, thunks: bool, prec: usize) -> Doc<'a> {
+    match term {
+        Var(x) if thunks => Owned(format!("{}()", scope.var(x))),
+        Var(x) => Owned(scope.var(x).to_string()),
+        App(_, _) => {
+            let mut args = vec![];
+            let mut head = term;
+            while let App(lhs, rhs) = head {
+                args.push(rhs);
+                head = lhs;
+            }
+            let mut spine = vec![to_doc(head, scope, thunks, 1)];
+            for arg in args.into_iter().rev() {
+                let arg = match &**arg {
+                    // The variable already holds a thunk.
+                    Var(x) if thunks => Owned(scope.var(x).to_string()),
+                    arg if thunks => Concat(vec![
+                        Text(&"() =>"),
+                        Doc::nest(2, Concat(vec![Line, to_doc(arg, scope, true, 0)])),
+                    ]),
+                    arg => to_doc(arg, scope, false, 0),
+                };
+                spine.push(Doc::group(Concat(vec![
+                    Text(&"("),
+                    Doc::nest(2, Concat(vec![Softline, arg])),
+                    Softline,
+                    Text(&")"),
+                ])));
+            }
+            Doc::group(Concat(spine))
+        }
+        Abs(_, _, _) => {
+            let mut binders = vec![];
+            let mut body = term;
+            let mut bound = 0;
+            while let Abs(x, _, e) = body {
+                if bound > 0 {
+                    binders.push(Text(&" "));
+                }
+                binders.push(Owned(scope.bind(x)));
+                binders.push(Text(&" =>"));
+                bound += 1;
+                body = e;
+            }
+            binders.push(Doc::nest(
+                2,
+                Concat(vec![Line, to_doc(body, scope, thunks, 0)]),
+            ));
+            for _ in 0..bound {
+                scope.unbind();
+            }
+            parens(Doc::group(Concat(binders)), prec > 0)
+        }
+        Pi(_, _, _) | Kind(_) => Text(&"null"),
+    }
+}
+
+impl Term<BareIdent> {
+    /// The term as a JavaScript expression, broken into lines of at most `width` columns where
+    /// possible. Free variables keep their mangled names.
+    pub fn to_js(&self, thunks: bool, width: usize) -> String {
+        let mut scope = Scope::new(self, mangle);
+        let mut out = String::new();
+        to_doc(self, &mut scope, thunks, 0)
+            .render(width, &mut out)
+            .unwrap();
+        out
+    }
+}
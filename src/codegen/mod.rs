@@ -0,0 +1,185 @@
+//! Compilation of terms to other languages.
+//!
+//! Closure conversion turns every abstraction into a function of a single argument, lifted to
+//! the top level, and a closure record of the variables it captures from its enclosing scope.
+//! Languages with closures of their own get the term as nested functions instead.
+//! Types have no computational content: binder domains are dropped, and products and sorts
+//! become a placeholder value.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::*;
+use crate::index::bare::BareIdent;
+use crate::index::uid::UID;
+
+pub mod c;
+pub mod js;
+pub mod scheme;
+
+#[derive(Clone, Debug)]
+pub enum CodegenError {
+    /// Only closed terms can be compiled, as nothing would define free variables.
+    Free(BareIdent),
+}
+
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Free(x) => {
+                write!(f, "`{}` is free, only closed terms can be compiled", x)
+            }
+        }
+    }
+}
+
+/// A term after closure conversion, where variables are either the argument of the function
+/// they occur in or one of its captures.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Expr {
+    Var(UID),
+    App(Box<Expr>, Box<Expr>),
+    /// A closure of the function of that index, capturing the given variables.
+    Closure(usize, Vec<UID>),
+    /// A product or a sort.
+    Type,
+}
+
+impl Expr {
+    /// Free variables, ordered by uid so the generated code is deterministic.
+    fn fv(&self, vars: &mut BTreeMap<usize, UID>) {
+        match self {
+            Expr::Var(x) => {
+                vars.insert(x.uid, x.clone());
+            }
+            Expr::App(e1, e2) => {
+                e1.fv(vars);
+                e2.fv(vars);
+            }
+            Expr::Closure(_, captures) => {
+                vars.extend(captures.iter().map(|x| (x.uid, x.clone())));
+            }
+            Expr::Type => {}
+        }
+    }
+}
+
+/// A lifted abstraction, which finds its captures in the closure it is called through.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Function {
+    pub param: UID,
+    pub captures: Vec<UID>,
+    pub body: Expr,
+}
+
+/// Functions are ordered so that each one only builds closures of the functions before it.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Program {
+    pub functions: Vec<Function>,
+    pub main: Expr,
+}
+
+fn convert(term: &Term<UID>, functions: &mut Vec<Function>) -> Expr {
+    match term {
+        Var(x) => Expr::Var(x.clone()),
+        App(e1, e2) => Expr::App(box convert(e1, functions), box convert(e2, functions)),
+        Abs(x, _, e) => {
+            let body = convert(e, functions);
+            let mut vars = BTreeMap::new();
+            body.fv(&mut vars);
+            vars.remove(&x.uid);
+            let captures: Vec<_> = vars.into_values().collect();
+            functions.push(Function {
+                param: x.clone(),
+                captures: captures.clone(),
+                body,
+            });
+            Expr::Closure(functions.len() - 1, captures)
+        }
+        Pi(_, _, _) | Kind(_) => Expr::Type,
+    }
+}
+
+impl Term<UID> {
+    /// Lift every abstraction of a closed term, after erasing its types. Variables which only
+    /// occur in types may be free.
+    pub fn closure_convert(&self) -> Result<Program, CodegenError> {
+        let mut functions = vec![];
+        let main = convert(self, &mut functions);
+        let mut free = BTreeMap::new();
+        main.fv(&mut free);
+        match free.into_values().next() {
+            Some(x) => Err(CodegenError::Free(x.name)),
+            None => Ok(Program { functions, main }),
+        }
+    }
+}
+
+/// The ASCII digit of a subscript digit, as in the names `Subscripts` makes up.
+fn subscript(c: char) -> Option<char> {
+    match c {
+        '₀'..='₉' => char::from_digit(c as u32 - '₀' as u32, 10),
+        _ => None,
+    }
+}
+
+/// Binders in scope and the identifiers they are emitted as. Names which aren't identifiers of
+/// the target language are mangled, then numbered if that made them clash with another name
+/// of the scope. Free variables keep their mangled names.
+struct Scope {
+    mangle: fn(&str) -> String,
+    free: Vec<(BareIdent, String)>,
+    bound: Vec<(BareIdent, String)>,
+}
+
+impl Scope {
+    fn new(term: &Term<BareIdent>, mangle: fn(&str) -> String) -> Self {
+        let mut free: Vec<_> = term
+            .fv()
+            .into_iter()
+            .map(|x| (x.clone(), mangle(x)))
+            .collect();
+        free.sort();
+        Scope {
+            mangle,
+            free,
+            bound: vec![],
+        }
+    }
+
+    fn var(&self, x: &BareIdent) -> &str {
+        let (_, ident) = self
+            .bound
+            .iter()
+            .rev()
+            .chain(self.free.iter())
+            .find(|(y, _)| y == x)
+            .unwrap();
+        ident
+    }
+
+    /// Bring `x` into scope, until the matching `unbind`.
+    fn bind(&mut self, x: &BareIdent) -> String {
+        let taken: HashSet<_> = self
+            .bound
+            .iter()
+            .chain(self.free.iter())
+            .filter(|(y, _)| y != x)
+            .map(|(_, ident)| ident.as_str())
+            .collect();
+        let mangled = (self.mangle)(x);
+        let mut ident = mangled.clone();
+        for i in 1.. {
+            if !taken.contains(ident.as_str()) {
+                break;
+            }
+            ident = format!("{}_{}", mangled, i);
+        }
+        self.bound.push((x.clone(), ident.clone()));
+        ident
+    }
+
+    fn unbind(&mut self) {
+        self.bound.pop();
+    }
+}
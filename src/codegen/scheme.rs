@@ -0,0 +1,103 @@
+//! Scheme lambdas, curried. A Church numeral `n` is read back with
+//! `((n (lambda (k) (+ k 1))) 0)`, after a first argument for its type in System F.
+//!
+//! With thunks, arguments are passed as `(lambda () arg)` and variables are called to get
+//! their value, which gives call-by-name. Decoders then take thunks as well:
+//! `((n (lambda () (lambda (k) (+ (k) 1)))) (lambda () 0))`.
+
+use std::fmt::Write;
+
+use crate::ast::*;
+use crate::codegen::{subscript, Scope};
+use crate::index::bare::BareIdent;
+use crate::pretty::list;
+use crate::pretty::Doc::{self, *};
+
+/// Syntactic keywords, which the emitted code relies on or which free variables could mean.
+const RESERVED: &[&str] = &[
+    "_",
+    "and",
+    "begin",
+    "case",
+    "cond",
+    "define",
+    "delay",
+    "do",
+    "else",
+    "force",
+    "if",
+    "lambda",
+    "let",
+    "letrec",
+    "or",
+    "quasiquote",
+    "quote",
+    "unless",
+    "unquote",
+    "when",
+];
+
+/// Primes become `*`, subscripts digits, and other symbols their code point after a `%`.
+/// Reserved words get a trailing `*`.
+fn mangle(name: &str) -> String {
+    let mut ident = String::new();
+    for c in name.chars() {
+        match c {
+            '\'' | '′' => ident.push('*'),
+            c if c == '_' || c.is_ascii_alphanumeric() || c.is_alphabetic() => ident.push(c),
+            c => match subscript(c) {
+                Some(digit) => ident.push(digit),
+                None => write!(ident, "%{:x}", c as u32).unwrap(),
+            },
+        }
+    }
+    if RESERVED.contains(&ident.as_str()) {
+        ident.push('*');
+    }
+    ident
+}
+
+/// `(lambda (params) body)`, the body going on its own line when it doesn't fit.
+fn lambda(params: String, body: Doc) -> Doc {
+    Doc::group(Concat(vec![
+        Owned(format!("(lambda ({})", params)),
+        Doc::nest(2, Concat(vec![Line, body])),
+        Text(&")"),
+    ]))
+}
+
+fn to_doc<'a>(term: &'a Term<BareIdent>, scope: &mut Scope, thunks: bool) -> Doc<'a> {
+    match term {
+        Var(x) if thunks => Owned(format!("({})", scope.var(x))),
+        Var(x) => Owned(scope.var(x).to_string()),
+        App(e1, e2) => {
+            let arg = match &**e2 {
+                // The variable already holds a thunk.
+                Var(x) if thunks => Owned(scope.var(x).to_string()),
+                e2 if thunks => lambda(String::new(), to_doc(e2, scope, true)),
+                e2 => to_doc(e2, scope, false),
+            };
+            list(vec![to_doc(e1, scope, thunks), arg])
+        }
+        Abs(x, _, e) => {
+            let x = scope.bind(x);
+            let body = to_doc(e, scope, thunks);
+            scope.unbind();
+            lambda(x, body)
+        }
+        Pi(_, _, _) | Kind(_) => Text(&"'()"),
+    }
+}
+
+impl Term<BareIdent> {
+    /// The term as a Scheme expression, broken into lines of at most `width` columns where
+    /// possible. Free variables keep their mangled names.
+    pub fn to_scheme(&self, thunks: bool, width: usize) -> String {
+        let mut scope = Scope::new(self, mangle);
+        let mut out = String::new();
+        to_doc(self, &mut scope, thunks)
+            .render(width, &mut out)
+            .unwrap();
+        out
+    }
+}
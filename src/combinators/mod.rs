@@ -0,0 +1,215 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::ast::*;
+use crate::index::bare::BareIdent;
+
+mod reduce;
+
+/// A term of combinatory logic.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Comb {
+    /// `S f g x = f x (g x)`
+    S,
+    /// `K x y = x`
+    K,
+    /// `I x = x`
+    I,
+    /// `B f g x = f (g x)`
+    B,
+    /// `C f g x = f x g`
+    C,
+    /// `W f x = f x x`
+    W,
+    Var(BareIdent),
+    App(Box<Comb>, Box<Comb>),
+}
+
+/// Combinators the translation may use.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Basis {
+    Ski,
+    /// `I` is written `W K`, and `S f g` is written `W (B (C f) g)`.
+    Bckw,
+}
+
+/// Bracket abstraction algorithm, from the largest output to the smallest.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Bracket {
+    /// `K` only for atoms, and `S` (or its BCKW form) for every application.
+    Naive,
+    /// `K` for every subterm without the variable, and `[x](M x) = M`.
+    Eta,
+    /// Turner's optimization of `Eta`, which applies `B` and `C` when the variable only occurs
+    /// on one side of an application. The SKI basis has them as `B f g = S (K f) g` and
+    /// `C f g = S f (K g)`, which is what `Eta` gives already.
+    Turner,
+}
+
+impl Default for Bracket {
+    fn default() -> Self {
+        Bracket::Turner
+    }
+}
+
+impl FromStr for Bracket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "naive" => Ok(Bracket::Naive),
+            "eta" => Ok(Bracket::Eta),
+            "turner" => Ok(Bracket::Turner),
+            _ => Err(format!("unknown bracket abstraction `{}`", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum CombError {
+    /// A product or a sort outside of a binder domain.
+    Typed(Term<BareIdent>),
+}
+
+impl Display for CombError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CombError::Typed(term) => write!(
+                f,
+                "`{}` is a type, only untyped terms translate to combinators",
+                term
+            ),
+        }
+    }
+}
+
+fn apply(lhs: Comb, rhs: Comb) -> Comb {
+    Comb::App(box lhs, box rhs)
+}
+
+impl Comb {
+    fn occurs(&self, x: &BareIdent) -> bool {
+        match self {
+            Comb::Var(y) => x == y,
+            Comb::App(lhs, rhs) => lhs.occurs(x) || rhs.occurs(x),
+            _ => false,
+        }
+    }
+
+    /// Translate a lambda term by abstracting the variables of its binders away, innermost
+    /// first. Binder domains are erased, and free variables are kept as they are.
+    pub fn from_term(
+        term: &Term<BareIdent>,
+        basis: Basis,
+        bracket: Bracket,
+    ) -> Result<Comb, CombError> {
+        match term {
+            Var(x) => Ok(Comb::Var(x.clone())),
+            App(lhs, rhs) => Ok(apply(
+                Comb::from_term(lhs, basis, bracket)?,
+                Comb::from_term(rhs, basis, bracket)?,
+            )),
+            Abs(x, _, e) => {
+                Ok(Comb::from_term(e, basis, bracket)?.abstract_over(x, basis, bracket))
+            }
+            Pi(_, _, _) | Kind(_) => Err(CombError::Typed(term.clone())),
+        }
+    }
+
+    /// `[x]self`, a term without `x` such that `[x]self x` reduces to `self`.
+    pub fn abstract_over(self, x: &BareIdent, basis: Basis, bracket: Bracket) -> Comb {
+        let atom = !matches!(self, Comb::App(_, _));
+        if !self.occurs(x) && (atom || bracket != Bracket::Naive) {
+            return apply(Comb::K, self);
+        }
+        match (self, basis) {
+            (Comb::Var(_), Basis::Ski) => Comb::I,
+            (Comb::Var(_), Basis::Bckw) => apply(Comb::W, Comb::K),
+            (Comb::App(lhs, rhs), _)
+                if bracket != Bracket::Naive && *rhs == Comb::Var(x.clone()) && !lhs.occurs(x) =>
+            {
+                *lhs
+            }
+            (Comb::App(lhs, rhs), _) if bracket == Bracket::Turner && !lhs.occurs(x) => {
+                let rhs = rhs.abstract_over(x, basis, bracket);
+                match basis {
+                    Basis::Ski => apply(apply(Comb::S, apply(Comb::K, *lhs)), rhs),
+                    Basis::Bckw => apply(apply(Comb::B, *lhs), rhs),
+                }
+            }
+            (Comb::App(lhs, rhs), _) if bracket == Bracket::Turner && !rhs.occurs(x) => {
+                let lhs = lhs.abstract_over(x, basis, bracket);
+                match basis {
+                    Basis::Ski => apply(apply(Comb::S, lhs), apply(Comb::K, *rhs)),
+                    Basis::Bckw => apply(apply(Comb::C, lhs), *rhs),
+                }
+            }
+            (Comb::App(lhs, rhs), Basis::Ski) => apply(
+                apply(Comb::S, lhs.abstract_over(x, basis, bracket)),
+                rhs.abstract_over(x, basis, bracket),
+            ),
+            (Comb::App(lhs, rhs), Basis::Bckw) => apply(
+                Comb::W,
+                apply(
+                    apply(
+                        Comb::B,
+                        apply(Comb::C, lhs.abstract_over(x, basis, bracket)),
+                    ),
+                    rhs.abstract_over(x, basis, bracket),
+                ),
+            ),
+            _ => unreachable!("Constants don't contain variables."),
+        }
+    }
+
+    /// Number of combinators and variables.
+    pub fn size(&self) -> usize {
+        match self {
+            Comb::App(lhs, rhs) => lhs.size() + rhs.size(),
+            _ => 1,
+        }
+    }
+
+    /// The lambda term obtained by replacing every combinator with its definition.
+    pub fn to_term(&self) -> Term<BareIdent> {
+        let var = |x: &str| Var(x.to_string());
+        let lam = |xs: &[&str], body| {
+            xs.iter()
+                .rev()
+                .fold(body, |body, x| abs(x.to_string(), Kind(Kinds::Star), body))
+        };
+        match self {
+            Comb::S => lam(
+                &["f", "g", "x"],
+                app(app(var("f"), var("x")), app(var("g"), var("x"))),
+            ),
+            Comb::K => lam(&["x", "y"], var("x")),
+            Comb::I => lam(&["x"], var("x")),
+            Comb::B => lam(&["f", "g", "x"], app(var("f"), app(var("g"), var("x")))),
+            Comb::C => lam(&["f", "g", "x"], app(app(var("f"), var("x")), var("g"))),
+            Comb::W => lam(&["f", "x"], app(app(var("f"), var("x")), var("x"))),
+            Comb::Var(x) => Var(x.clone()),
+            Comb::App(lhs, rhs) => app(lhs.to_term(), rhs.to_term()),
+        }
+    }
+}
+
+/// Applications associate to the left, so only arguments which are applications get
+/// parenthesized, as in `S (K S) K`.
+impl Display for Comb {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Comb::S => write!(f, "S"),
+            Comb::K => write!(f, "K"),
+            Comb::I => write!(f, "I"),
+            Comb::B => write!(f, "B"),
+            Comb::C => write!(f, "C"),
+            Comb::W => write!(f, "W"),
+            Comb::Var(x) => write!(f, "{}", x),
+            Comb::App(lhs, rhs) => match **rhs {
+                Comb::App(_, _) => write!(f, "{} ({})", lhs, rhs),
+                _ => write!(f, "{} {}", lhs, rhs),
+            },
+        }
+    }
+}
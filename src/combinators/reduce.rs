@@ -0,0 +1,75 @@
+use crate::combinators::{apply, Comb};
+use crate::index::uid::DEFAULT_LIMIT;
+
+impl Comb {
+    /// Number of arguments the combinator needs to be contracted.
+    fn arity(&self) -> Option<usize> {
+        match self {
+            Comb::I => Some(1),
+            Comb::K | Comb::W => Some(2),
+            Comb::S | Comb::B | Comb::C => Some(3),
+            Comb::Var(_) | Comb::App(_, _) => None,
+        }
+    }
+
+    /// Contract the leftmost outermost redex, if there is one.
+    pub fn step(&self) -> Option<Comb> {
+        let mut args = vec![];
+        let mut head = self;
+        while let Comb::App(lhs, rhs) = head {
+            args.push(&**rhs);
+            head = lhs;
+        }
+        args.reverse();
+        match head.arity() {
+            Some(arity) if args.len() >= arity => {
+                let arg = |i: usize| args[i].clone();
+                let contracted = match head {
+                    Comb::I | Comb::K => arg(0),
+                    Comb::S => apply(apply(arg(0), arg(2)), apply(arg(1), arg(2))),
+                    Comb::B => apply(arg(0), apply(arg(1), arg(2))),
+                    Comb::C => apply(apply(arg(0), arg(2)), arg(1)),
+                    Comb::W => apply(apply(arg(0), arg(1)), arg(1)),
+                    _ => unreachable!(),
+                };
+                Some(
+                    args[arity..]
+                        .iter()
+                        .map(|arg| (*arg).clone())
+                        .fold(contracted, apply),
+                )
+            }
+            // The head is stuck, so reduce the arguments from left to right.
+            _ => {
+                let (i, reduced) = args
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, arg)| arg.step().map(|reduced| (i, reduced)))?;
+                Some(args.iter().enumerate().fold(head.clone(), |e, (j, arg)| {
+                    apply(
+                        e,
+                        if i == j {
+                            reduced.clone()
+                        } else {
+                            (*arg).clone()
+                        },
+                    )
+                }))
+            }
+        }
+    }
+
+    /// Reduce in normal order for at most `limit` steps, `DEFAULT_LIMIT` by default, and tell
+    /// whether the result is normal.
+    pub fn reduce(&self, limit: Option<usize>) -> (Comb, bool) {
+        let mut term = self.clone();
+        for _ in 0..limit.unwrap_or(DEFAULT_LIMIT) {
+            match term.step() {
+                Some(next) => term = next,
+                None => return (term, true),
+            }
+        }
+        let normal = term.step().is_none();
+        (term, normal)
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::ast::*;
+use crate::index::bare::BareIdent;
+
+pub use read::Value;
+
+mod read;
+
+/// How numeral, boolean and list literals are elaborated.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Encoding {
+    /// Church encodings with System F types, e.g. `2 : ∀A:*, (A → A) → A → A` and
+    /// `[a, b : T] : ∀R:*, (T → R → R) → R → R`.
+    Church,
+    /// Church encodings without types, like those of the untyped prelude, e.g.
+    /// `2 = λf x:*.f (f x)` and `[a, b] = λc n:*.c a (c b n)`.
+    Untyped,
+    /// Scott encodings. Typing them needs recursive types, so all of their binders are `*`.
+    Scott,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::Church
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "church" => Ok(Encoding::Church),
+            "scott" => Ok(Encoding::Scott),
+            _ => Err(format!("unknown encoding `{}`", s)),
+        }
+    }
+}
+
+fn var(x: &str) -> Term<BareIdent> {
+    Var(x.to_string())
+}
+
+fn lam(x: &str, ty: Ty<BareIdent>, body: Term<BareIdent>) -> Term<BareIdent> {
+    abs(x.to_string(), ty, body)
+}
+
+fn arrow(lty: Ty<BareIdent>, rty: Ty<BareIdent>) -> Ty<BareIdent> {
+    Pi("_".to_string(), box lty, box rty)
+}
+
+fn star() -> Ty<BareIdent> {
+    Kind(Kinds::Star)
+}
+
+/// `name`, or the first of its fresh variants that isn't `taken`.
+fn avoid(name: &str, taken: &HashSet<&BareIdent>) -> BareIdent {
+    let mut name = name.to_string();
+    while taken.contains(&name) {
+        name = name.fresh();
+    }
+    name
+}
+
+impl Encoding {
+    pub fn nat(self, n: usize) -> Term<BareIdent> {
+        match self {
+            Encoding::Church => lam(
+                "A",
+                star(),
+                lam(
+                    "f",
+                    arrow(var("A"), var("A")),
+                    lam(
+                        "x",
+                        var("A"),
+                        (0..n).fold(var("x"), |e, _| app(var("f"), e)),
+                    ),
+                ),
+            ),
+            Encoding::Untyped => lam(
+                "f",
+                star(),
+                lam("x", star(), (0..n).fold(var("x"), |e, _| app(var("f"), e))),
+            ),
+            Encoding::Scott => (0..n)
+                .fold(lam("s", star(), lam("z", star(), var("z"))), |pred, _| {
+                    lam("s", star(), lam("z", star(), app(var("s"), pred)))
+                }),
+        }
+    }
+
+    pub fn boolean(self, b: bool) -> Term<BareIdent> {
+        let body = var(if b { "t" } else { "f" });
+        match self {
+            Encoding::Church => lam("A", star(), lam("t", var("A"), lam("f", var("A"), body))),
+            Encoding::Untyped | Encoding::Scott => lam("t", star(), lam("f", star(), body)),
+        }
+    }
+
+    /// A list of `elems`, whose type defaults to `*` as with bare binders.
+    ///
+    /// Binders introduced by the encoding are renamed away from the free variables of the
+    /// elements. Untyped and Scott lists ignore the element type.
+    pub fn list(self, elems: Vec<Term<BareIdent>>, ty: Option<Ty<BareIdent>>) -> Term<BareIdent> {
+        let ty = ty.unwrap_or_else(star);
+        let taken: HashSet<_> = elems.iter().chain(Some(&ty)).flat_map(Term::fv).collect();
+        let (r, c, n) = (avoid("R", &taken), avoid("c", &taken), avoid("n", &taken));
+        match self {
+            Encoding::Church => {
+                let body = elems
+                    .into_iter()
+                    .rev()
+                    .fold(var(&n), |tail, e| app(app(var(&c), e), tail));
+                lam(
+                    &r,
+                    star(),
+                    lam(
+                        &c,
+                        arrow(ty, arrow(var(&r), var(&r))),
+                        lam(&n, var(&r), body),
+                    ),
+                )
+            }
+            Encoding::Untyped => {
+                let body = elems
+                    .into_iter()
+                    .rev()
+                    .fold(var(&n), |tail, e| app(app(var(&c), e), tail));
+                lam(&c, star(), lam(&n, star(), body))
+            }
+            Encoding::Scott => elems
+                .into_iter()
+                .rev()
+                .fold(lam(&c, star(), lam(&n, star(), var(&n))), |tail, e| {
+                    lam(&c, star(), lam(&n, star(), app(app(var(&c), e), tail)))
+                }),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::*;
+use crate::encoding::Encoding;
+
+/// Data decoded from the normal form of an encoding.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Value<T: IdentType> {
+    Nat(usize),
+    Bool(bool),
+    Pair(Box<Value<T>>, Box<Value<T>>),
+    List(Vec<Value<T>>),
+    /// A term which doesn't encode any data.
+    Term(Term<T>),
+}
+
+impl<T: IdentType> Display for Value<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nat(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Pair(a, b) => write!(f, "pair({},{})", a, b),
+            Value::List(elems) => {
+                write!(f, "[")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            }
+            Value::Term(term) => write!(f, "{}", term),
+        }
+    }
+}
+
+/// Views of `term` as an encoding, either untyped, or under the type abstraction of its
+/// System F variant.
+fn views<T: IdentType>(term: &Term<T>) -> Vec<(Option<&T>, &Term<T>)> {
+    let mut views = vec![(None, term)];
+    if let Abs(a, ty, body) = term {
+        if let Kind(Kinds::Star) = **ty {
+            views.push((Some(a), &**body));
+        }
+    }
+    views
+}
+
+/// Strip a binder of `term`, whose type is `*` for untyped encodings, or a function type
+/// with `arity` arguments into the type variable `a` otherwise.
+fn binder<'a, T: IdentType>(
+    term: &'a Term<T>,
+    a: Option<&T>,
+    arity: usize,
+) -> Option<(&'a T, &'a Term<T>)> {
+    if let Abs(x, ty, body) = term {
+        let mut ty = &**ty;
+        let result = match a {
+            None => *ty == Kind(Kinds::Star),
+            Some(a) => {
+                for _ in 0..arity {
+                    ty = if let Pi(_, _, rty) = ty {
+                        &**rty
+                    } else {
+                        return None;
+                    };
+                }
+                matches!(ty, Var(v) if v == a) && x != a
+            }
+        };
+        if result {
+            return Some((x, &**body));
+        }
+    }
+    None
+}
+
+fn is_var<T: IdentType>(term: &Term<T>, x: &T) -> bool {
+    matches!(term, Var(v) if v == x)
+}
+
+/// Whether `term` could be moved out of the scope of the binders `xs`.
+fn free_of<T: IdentType>(term: &Term<T>, xs: &[&T]) -> bool {
+    let fv = term.fv();
+    xs.iter().all(|x| !fv.contains(x))
+}
+
+impl Encoding {
+    pub fn read_nat<T: IdentType>(self, term: &Term<T>) -> Option<usize> {
+        match self {
+            Encoding::Church | Encoding::Untyped => {
+                views(term).into_iter().find_map(|(a, term)| {
+                    let (f, term) = binder(term, a, 1)?;
+                    let (x, mut body) = binder(term, a, 0)?;
+                    let mut n = 0;
+                    loop {
+                        match body {
+                            Var(v) if v == x => return Some(n),
+                            App(g, arg) if is_var(g, f) && f != x => {
+                                n += 1;
+                                body = &**arg;
+                            }
+                            _ => return None,
+                        }
+                    }
+                })
+            }
+            Encoding::Scott => {
+                let (s, term) = binder(term, None, 0)?;
+                let (z, body) = binder(term, None, 0)?;
+                match body {
+                    Var(v) if v == z => Some(0),
+                    App(g, pred) if is_var(g, s) && s != z && free_of(pred, &[s, z]) => {
+                        Some(self.read_nat(pred)? + 1)
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Booleans are encoded alike in all encodings.
+    pub fn read_bool<T: IdentType>(self, term: &Term<T>) -> Option<bool> {
+        views(term).into_iter().find_map(|(a, term)| {
+            let (t, term) = binder(term, a, 0)?;
+            let (f, body) = binder(term, a, 0)?;
+            match body {
+                Var(v) if v == f => Some(false),
+                Var(v) if v == t => Some(true),
+                _ => None,
+            }
+        })
+    }
+
+    /// Pairs `λp.p a b` are encoded alike in all encodings.
+    pub fn read_pair<T: IdentType>(self, term: &Term<T>) -> Option<(&Term<T>, &Term<T>)> {
+        views(term).into_iter().find_map(|(a, term)| {
+            let (p, body) = binder(term, a, 2)?;
+            match body {
+                App(e, snd) => match &**e {
+                    App(g, fst) if is_var(g, p) && free_of(fst, &[p]) && free_of(snd, &[p]) => {
+                        Some((&**fst, &**snd))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            }
+        })
+    }
+
+    pub fn read_list<T: IdentType>(self, term: &Term<T>) -> Option<Vec<&Term<T>>> {
+        match self {
+            Encoding::Church | Encoding::Untyped => {
+                views(term).into_iter().find_map(|(a, term)| {
+                    let (c, term) = binder(term, a, 2)?;
+                    let (n, mut body) = binder(term, a, 0)?;
+                    let mut elems = vec![];
+                    loop {
+                        match body {
+                            Var(v) if v == n => return Some(elems),
+                            App(e, tail) if c != n => match &**e {
+                                App(g, head) if is_var(g, c) && free_of(head, &[c, n]) => {
+                                    elems.push(&**head);
+                                    body = &**tail;
+                                }
+                                _ => return None,
+                            },
+                            _ => return None,
+                        }
+                    }
+                })
+            }
+            Encoding::Scott => {
+                let (c, term) = binder(term, None, 0)?;
+                let (n, body) = binder(term, None, 0)?;
+                match body {
+                    Var(v) if v == n => Some(vec![]),
+                    App(e, tail) if c != n && free_of(tail, &[c, n]) => match &**e {
+                        App(g, head) if is_var(g, c) && free_of(head, &[c, n]) => {
+                            let mut elems = vec![&**head];
+                            elems.extend(self.read_list(tail)?);
+                            Some(elems)
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Decode a normal form, trying numerals, booleans, pairs and lists in turn.
+    ///
+    /// Untyped encodings overlap, e.g. `λf x.x` is both `0` and `false`, and reads as `0`.
+    pub fn read<T: IdentType>(self, term: &Term<T>) -> Value<T> {
+        if let Some(n) = self.read_nat(term) {
+            Value::Nat(n)
+        } else if let Some(b) = self.read_bool(term) {
+            Value::Bool(b)
+        } else if let Some((fst, snd)) = self.read_pair(term) {
+            Value::Pair(box self.read(fst), box self.read(snd))
+        } else if let Some(elems) = self.read_list(term) {
+            Value::List(elems.into_iter().map(|elem| self.read(elem)).collect())
+        } else {
+            Value::Term(term.clone())
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use pest::error::Error;
+
+use crate::ast::*;
+use crate::encoding::Encoding;
+use crate::index::bare::BareIdent;
+use crate::index::uid::{Conversion, TypeError, UID};
+use crate::parser::{parse_module, parse_with, Rule};
+
+/// Definitions and axioms accumulated while processing declarations in order.
+#[derive(Clone, Debug, Default)]
+pub struct Env {
+    axioms: HashMap<BareIdent, Ty<UID>>,
+    defs: HashMap<BareIdent, Term<UID>>,
+    pub limit: Option<usize>,
+    /// How literals are elaborated, so that they agree with the prelude.
+    pub encoding: Encoding,
+}
+
+#[derive(Clone, Debug)]
+pub enum Response {
+    Defined(BareIdent),
+    Assumed(BareIdent, Ty<BareIdent>),
+    /// The result is displayed as the data it encodes, if any.
+    Evaluated(Term<BareIdent>, Term<BareIdent>),
+    Checked(Term<BareIdent>, Ty<BareIdent>),
+    Asserted(Term<BareIdent>, Term<BareIdent>),
+}
+
+impl Display for Response {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Response::Defined(name) => write!(f, "{} defined", name),
+            Response::Assumed(name, ty) => write!(f, "{} : {} assumed", name, ty),
+            Response::Evaluated(term, result) => {
+                write!(f, "{} => {}", term, Encoding::default().read(result))
+            }
+            Response::Checked(term, ty) => write!(f, "{} : {}", term, ty),
+            Response::Asserted(lhs, rhs) => write!(f, "{} == {}", lhs, rhs),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EnvError {
+    Parse(Error<Rule>),
+    Redefined(BareIdent),
+    Type(TypeError),
+    AssertionFailed(Term<BareIdent>, Term<BareIdent>, Conversion),
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::Parse(e) => write!(f, "{}", e),
+            EnvError::Redefined(name) => write!(f, "`{}` is already defined", name),
+            EnvError::Type(e) => write!(f, "type error: {}", e),
+            EnvError::AssertionFailed(lhs, rhs, Conversion::Unknown(limit)) => write!(
+                f,
+                "assertion failed: can't decide `{} == {}` within {} steps",
+                lhs, rhs, limit
+            ),
+            EnvError::AssertionFailed(lhs, rhs, _) => {
+                write!(f, "assertion failed: `{}` is not `{}`", lhs, rhs)
+            }
+        }
+    }
+}
+
+impl From<Error<Rule>> for EnvError {
+    fn from(e: Error<Rule>) -> Self {
+        EnvError::Parse(e)
+    }
+}
+
+impl From<TypeError> for EnvError {
+    fn from(e: TypeError) -> Self {
+        EnvError::Type(e)
+    }
+}
+
+impl Env {
+    /// Parse a term, elaborating its literals with the encoding of the environment.
+    pub fn parse(&self, source: &str) -> Result<Term<BareIdent>, Error<Rule>> {
+        parse_with(source, self.encoding)
+    }
+
+    /// Resolve a surface term against the environment, unfolding all definitions it mentions.
+    pub fn elaborate(&self, term: &Term<BareIdent>) -> Term<UID> {
+        Term::<UID>::from(term.clone()).unfold(&self.defs)
+    }
+
+    pub fn type_of(&self, term: &Term<BareIdent>) -> Result<Ty<UID>, TypeError> {
+        self.elaborate(term).type_of(&self.axioms)
+    }
+
+    fn declare(&self, name: &BareIdent) -> Result<(), EnvError> {
+        if self.defs.contains_key(name) || self.axioms.contains_key(name) {
+            Err(EnvError::Redefined(name.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn process(&mut self, decl: Decl<BareIdent>) -> Result<Response, EnvError> {
+        match decl {
+            Decl::Def(name, ty, term) => {
+                self.declare(&name)?;
+                let term_ = self.elaborate(&term);
+                if let Some(ty) = ty {
+                    term_.check(&self.elaborate(&ty), &self.axioms)?;
+                }
+                self.defs.insert(name.clone(), term_);
+                Ok(Response::Defined(name))
+            }
+            Decl::Axiom(name, ty) => {
+                self.declare(&name)?;
+                let ty_ = self.elaborate(&ty);
+                ty_.type_of(&self.axioms)?;
+                self.axioms.insert(name.clone(), ty_);
+                Ok(Response::Assumed(name, ty))
+            }
+            Decl::Eval(term, strategy) => {
+                let result = self
+                    .elaborate(&term)
+                    .beta_reduce(strategy.unwrap_or(ReduceStrategy::NOR), self.limit);
+                Ok(Response::Evaluated(term, Term::from(result)))
+            }
+            Decl::Check(term) => {
+                let ty = self.type_of(&term)?;
+                Ok(Response::Checked(term, Term::from(ty)))
+            }
+            Decl::Assert(lhs, rhs) => {
+                match self
+                    .elaborate(&lhs)
+                    .convertible(&self.elaborate(&rhs), self.limit)
+                {
+                    Conversion::Equal => Ok(Response::Asserted(lhs, rhs)),
+                    conversion => Err(EnvError::AssertionFailed(lhs, rhs, conversion)),
+                }
+            }
+        }
+    }
+
+    /// Process every declaration of a source file in order, stopping at the first error.
+    pub fn load(&mut self, source: &str) -> Result<Vec<Response>, EnvError> {
+        parse_module(source)?
+            .into_iter()
+            .map(|decl| self.process(decl))
+            .collect()
+    }
+}
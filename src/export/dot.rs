@@ -0,0 +1,49 @@
+use std::fmt::Write;
+
+use super::{flatten, Child};
+use crate::ast::*;
+
+/// Quote `label` as a DOT string.
+fn quote(label: &str) -> String {
+    format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl<T: IdentType> Term<T> {
+    /// The syntax tree as a Graphviz digraph, with a dashed edge from every bound variable back
+    /// to its binder. The subterm at `redex`, if any, is filled in.
+    pub fn to_dot(&self, redex: Option<&[Child]>) -> String {
+        let nodes = flatten(self, redex);
+        let mut out = String::new();
+        writeln!(out, "digraph term {{").unwrap();
+        writeln!(out, "  node [shape=ellipse, fontname=\"monospace\"];").unwrap();
+        for (id, node) in nodes.iter().enumerate() {
+            let style = if node.highlighted {
+                ", style=filled, fillcolor=\"#ffe08a\""
+            } else {
+                ""
+            };
+            writeln!(out, "  n{} [label={}{}];", id, quote(&node.label), style).unwrap();
+        }
+        for (id, node) in nodes.iter().enumerate() {
+            for (child, label) in &node.children {
+                if label.is_empty() {
+                    writeln!(out, "  n{} -> n{};", id, child).unwrap();
+                } else {
+                    writeln!(out, "  n{} -> n{} [label={}];", id, child, quote(label)).unwrap();
+                }
+            }
+        }
+        for (id, node) in nodes.iter().enumerate() {
+            if let Some(binder) = node.binder {
+                writeln!(
+                    out,
+                    "  n{} -> n{} [style=dashed, constraint=false, color=gray];",
+                    id, binder
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
@@ -0,0 +1,135 @@
+use crate::ast::*;
+
+pub mod dot;
+pub mod svg;
+pub mod tromp;
+
+/// A step from a term to one of its immediate subterms.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Child {
+    /// The function of an application.
+    Fun,
+    /// The argument of an application.
+    Arg,
+    /// The domain of an abstraction or a product.
+    Ty,
+    /// The body of an abstraction or a product.
+    Body,
+}
+
+/// The position of a subterm, as the steps leading to it from the root.
+pub type Path = Vec<Child>;
+
+impl<T: IdentType> Term<T> {
+    /// The subterm at `path`, if there is one.
+    pub fn at(&self, path: &[Child]) -> Option<&Term<T>> {
+        match (path.split_first(), self) {
+            (None, _) => Some(self),
+            (Some((Child::Fun, path)), App(e, _))
+            | (Some((Child::Arg, path)), App(_, e))
+            | (Some((Child::Ty, path)), Abs(_, e, _))
+            | (Some((Child::Ty, path)), Pi(_, e, _))
+            | (Some((Child::Body, path)), Abs(_, _, e))
+            | (Some((Child::Body, path)), Pi(_, _, e)) => e.at(path),
+            _ => None,
+        }
+    }
+
+    /// Paths to every beta redex, leftmost outermost first.
+    pub fn redexes(&self) -> Vec<Path> {
+        let mut redexes = vec![];
+        collect_redexes(self, &mut vec![], &mut redexes);
+        redexes
+    }
+}
+
+fn collect_redexes<T: IdentType>(term: &Term<T>, path: &mut Path, redexes: &mut Vec<Path>) {
+    let children: Vec<(Child, &Term<T>)> = match term {
+        App(e1, e2) => {
+            if let Abs(_, _, _) = **e1 {
+                redexes.push(path.clone());
+            }
+            vec![(Child::Fun, e1), (Child::Arg, e2)]
+        }
+        Abs(_, ty, e) | Pi(_, ty, e) => vec![(Child::Ty, ty), (Child::Body, e)],
+        Var(_) | Kind(_) => vec![],
+    };
+    for (child, e) in children {
+        path.push(child);
+        collect_redexes(e, path, redexes);
+        path.pop();
+    }
+}
+
+/// A node of the syntax tree, numbered in pre-order.
+struct Node {
+    label: String,
+    depth: usize,
+    /// Children along with the label of the edge leading to them.
+    children: Vec<(usize, &'static str)>,
+    /// The binder of a bound variable.
+    binder: Option<usize>,
+    /// Whether the node belongs to the highlighted redex.
+    highlighted: bool,
+}
+
+/// Flatten `term`, resolving every variable to its innermost binder in scope.
+fn flatten<T: IdentType>(term: &Term<T>, redex: Option<&[Child]>) -> Vec<Node> {
+    let mut nodes = vec![];
+    walk(term, 0, &mut vec![], redex, false, &mut nodes);
+    nodes
+}
+
+/// `redex` is the rest of the path to the highlighted redex, while `inside` tells whether it
+/// has already been reached.
+fn walk<'a, T: IdentType>(
+    term: &'a Term<T>,
+    depth: usize,
+    scope: &mut Vec<(&'a T, usize)>,
+    redex: Option<&[Child]>,
+    inside: bool,
+    nodes: &mut Vec<Node>,
+) -> usize {
+    let id = nodes.len();
+    let inside = inside || redex.map_or(false, |path| path.is_empty());
+    let (label, binder) = match term {
+        Var(x) => (
+            x.to_string(),
+            scope.iter().rev().find(|(y, _)| *y == x).map(|(_, id)| *id),
+        ),
+        App(_, _) => ("@".to_string(), None),
+        Abs(x, _, _) => (format!("λ{}", x), None),
+        Pi(x, _, e) if !e.fv().contains(x) => ("→".to_string(), None),
+        Pi(x, _, _) => (format!("π{}", x), None),
+        Kind(kind) => (kind.to_string(), None),
+    };
+    nodes.push(Node {
+        label,
+        depth,
+        children: vec![],
+        binder,
+        highlighted: inside,
+    });
+    let children: Vec<(Child, &Term<T>, Option<&T>, &'static str)> = match term {
+        App(e1, e2) => vec![(Child::Fun, e1, None, ""), (Child::Arg, e2, None, "")],
+        Abs(x, ty, e) | Pi(x, ty, e) => {
+            vec![(Child::Ty, ty, None, ":"), (Child::Body, e, Some(x), "")]
+        }
+        Var(_) | Kind(_) => vec![],
+    };
+    for (child, e, bound, edge) in children {
+        let redex = match redex {
+            Some([step, rest @ ..]) if *step == child => Some(rest),
+            _ => None,
+        };
+        if let Some(x) = bound {
+            scope.push((x, id));
+        }
+        let child_id = walk(e, depth + 1, scope, redex, inside, nodes);
+        if bound.is_some() {
+            scope.pop();
+        }
+        nodes[id].children.push((child_id, edge));
+    }
+    id
+}
@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use super::{flatten, Child, Node};
+use crate::ast::*;
+
+/// Horizontal distance between neighbouring leaves.
+const DX: f64 = 56.0;
+/// Vertical distance between levels.
+const DY: f64 = 64.0;
+const MARGIN: f64 = 32.0;
+const FONT_SIZE: f64 = 14.0;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Place leaves on consecutive columns and every other node centered above its children.
+fn layout(nodes: &[Node], id: usize, next: &mut f64, xs: &mut [f64]) {
+    let children = &nodes[id].children;
+    if children.is_empty() {
+        xs[id] = *next;
+        *next += 1.0;
+    } else {
+        for (child, _) in children {
+            layout(nodes, *child, next, xs);
+        }
+        xs[id] = (xs[children[0].0] + xs[children[children.len() - 1].0]) / 2.0;
+    }
+}
+
+/// Half the width of the ellipse around `label`.
+fn radius(label: &str) -> f64 {
+    (label.chars().count() as f64 * FONT_SIZE * 0.3 + 8.0).max(16.0)
+}
+
+impl<T: IdentType> Term<T> {
+    /// The syntax tree as a self-contained SVG image, drawn like `to_dot`.
+    pub fn to_svg(&self, redex: Option<&[Child]>) -> String {
+        let nodes = flatten(self, redex);
+        let mut xs = vec![0.0; nodes.len()];
+        let mut columns = 0.0;
+        layout(&nodes, 0, &mut columns, &mut xs);
+        let depth = nodes.iter().map(|node| node.depth).max().unwrap_or(0);
+        let pos = |id: usize| (MARGIN + xs[id] * DX, MARGIN + nodes[id].depth as f64 * DY);
+        let width = 2.0 * MARGIN + (columns - 1.0) * DX;
+        let height = 2.0 * MARGIN + depth as f64 * DY;
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+             viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-size=\"{2}\">",
+            width, height, FONT_SIZE
+        )
+        .unwrap();
+        for (id, node) in nodes.iter().enumerate() {
+            let (x1, y1) = pos(id);
+            for (child, label) in &node.children {
+                let (x2, y2) = pos(*child);
+                writeln!(
+                    out,
+                    "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+                    x1, y1, x2, y2
+                )
+                .unwrap();
+                if !label.is_empty() {
+                    writeln!(
+                        out,
+                        "  <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+                        (x1 + x2) / 2.0 - 4.0,
+                        (y1 + y2) / 2.0,
+                        escape(label)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        for (id, node) in nodes.iter().enumerate() {
+            if let Some(binder) = node.binder {
+                let (x1, y1) = pos(id);
+                let (x2, y2) = pos(binder);
+                // Bend to the right of the tree edges running between the two nodes.
+                let bend = DX / 2.0 + (y1 - y2) / 4.0;
+                writeln!(
+                    out,
+                    "  <path d=\"M {} {} Q {} {} {} {}\" fill=\"none\" stroke=\"gray\" \
+                     stroke-dasharray=\"4 3\"/>",
+                    x1,
+                    y1,
+                    x1.max(x2) + bend,
+                    (y1 + y2) / 2.0,
+                    x2,
+                    y2
+                )
+                .unwrap();
+            }
+        }
+        for (id, node) in nodes.iter().enumerate() {
+            let (x, y) = pos(id);
+            let fill = if node.highlighted { "#ffe08a" } else { "white" };
+            writeln!(
+                out,
+                "  <ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"14\" fill=\"{}\" stroke=\"black\"/>",
+                x,
+                y,
+                radius(&node.label),
+                fill
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                x,
+                y,
+                escape(&node.label)
+            )
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
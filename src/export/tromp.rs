@@ -0,0 +1,246 @@
+//! John Tromp's lambda diagrams: abstractions are horizontal bars, variables are vertical lines
+//! hanging from the bar of their binder, and applications are horizontal links between the
+//! leftmost lines of the function and the argument.
+//!
+//! Binder domains are not drawn, products are drawn as abstractions, and free variables and
+//! sorts hang from the top edge.
+
+use std::fmt::Write;
+
+use crate::ast::*;
+
+/// Side of a grid cell in SVG pixels, a line is half as thick.
+const CELL: usize = 8;
+/// Seconds each step is shown for in animations.
+const FRAME_SECONDS: f64 = 1.0;
+
+/// A diagram on a grid where variable `k` occupies column `k` and rows grow downwards.
+#[derive(Clone, Debug, Default)]
+pub struct Diagram {
+    /// Bars of abstractions, as `(row, first column, last column)`.
+    lambdas: Vec<(usize, usize, usize)>,
+    /// Lines of variables, as `(column, top row, bottom row)`.
+    vars: Vec<(usize, usize, usize)>,
+    /// Links of applications, as `(row, left column, right column)`.
+    links: Vec<(usize, usize, usize)>,
+    /// Number of columns.
+    width: usize,
+    /// Row the output line of the whole term ends at.
+    height: usize,
+}
+
+impl Diagram {
+    pub fn new<T: IdentType>(term: &Term<T>) -> Self {
+        let mut diagram = Diagram::default();
+        // Leave row 0 for the free variables, and drop it again if there are none.
+        let (width, height, _) = diagram.draw(term, 1, 0, &mut vec![]);
+        diagram.width = width;
+        diagram.height = height;
+        if diagram.vars.iter().all(|(_, top, _)| *top > 0) {
+            diagram.shift();
+        }
+        diagram
+    }
+
+    fn shift(&mut self) {
+        for (row, _, _) in self.lambdas.iter_mut().chain(self.links.iter_mut()) {
+            *row -= 1;
+        }
+        for (_, top, bottom) in &mut self.vars {
+            *top -= 1;
+            *bottom -= 1;
+        }
+        self.height -= 1;
+    }
+
+    /// Draw `term` with its top at `row` and its leftmost column at `col`, and return its width,
+    /// its bottom row, and the index of its output line, which is the leftmost one.
+    fn draw<'a, T: IdentType>(
+        &mut self,
+        term: &'a Term<T>,
+        row: usize,
+        col: usize,
+        scope: &mut Vec<(&'a T, usize)>,
+    ) -> (usize, usize, usize) {
+        match term {
+            Var(_) | Kind(_) => {
+                let top = match term {
+                    Var(x) => scope
+                        .iter()
+                        .rev()
+                        .find(|(y, _)| *y == x)
+                        .map(|(_, row)| *row),
+                    _ => None,
+                };
+                self.vars.push((col, top.unwrap_or(0), row));
+                (1, row, self.vars.len() - 1)
+            }
+            Abs(x, _, e) | Pi(x, _, e) => {
+                scope.push((x, row));
+                let (width, bottom, output) = self.draw(e, row + 1, col, scope);
+                scope.pop();
+                self.lambdas.push((row, col, col + width - 1));
+                (width, bottom, output)
+            }
+            App(e1, e2) => {
+                let (width1, bottom1, output1) = self.draw(e1, row, col, scope);
+                let (width2, bottom2, output2) = self.draw(e2, row, col + width1, scope);
+                let link = bottom1.max(bottom2);
+                self.links.push((link, col, col + width1));
+                self.vars[output1].2 = link + 1;
+                self.vars[output2].2 = link;
+                (width1 + width2, link + 1, output1)
+            }
+        }
+    }
+
+    /// The diagram in box-drawing characters, two characters per column and per row.
+    pub fn to_text(&self) -> String {
+        // Which of its up, down, left and right sides each cell connects.
+        let mut cells = vec![vec![[false; 4]; 2 * self.width + 1]; 2 * self.height + 1];
+        for (row, left, right) in &self.links {
+            horizontal(&mut cells, 2 * row, 2 * left + 1, 2 * right + 1);
+        }
+        // Bars stick out of their outer lines by half a character, so that the bars of
+        // neighbouring abstractions stay apart.
+        for (row, first, last) in &self.lambdas {
+            horizontal(&mut cells, 2 * row, 2 * first + 1, 2 * last + 1);
+            cells[2 * row][2 * first + 1][2] = true;
+            cells[2 * row][2 * last + 1][3] = true;
+        }
+        for (col, top, bottom) in &self.vars {
+            for row in 2 * top..2 * bottom {
+                cells[row][2 * col + 1][1] = true;
+                cells[row + 1][2 * col + 1][0] = true;
+            }
+        }
+        let mut out = String::new();
+        for line in cells {
+            let line: String = line.iter().map(|cell| box_char(*cell)).collect();
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    fn svg_size(&self) -> (usize, usize) {
+        (CELL * (2 * self.width + 1), CELL * (2 * self.height + 1))
+    }
+
+    /// The shapes of the diagram as SVG rectangles.
+    fn svg_shapes(&self, indent: &str, out: &mut String) {
+        let mut rect = |x: usize, y: usize, w: usize, h: usize| {
+            writeln!(
+                out,
+                "{}<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                indent, x, y, w, h
+            )
+            .unwrap()
+        };
+        // Cell (row, col) of `to_text` spans [CELL * col, CELL * (col + 1)) horizontally, and
+        // lines run through the middle quarter of their cells.
+        let (thin, offset) = (CELL / 2, CELL / 4);
+        for (row, first, last) in &self.lambdas {
+            let (x, y) = (CELL * 2 * first + 3 * offset, CELL * 2 * row + offset);
+            rect(
+                x,
+                y,
+                CELL * 2 * (last - first) + 2 * CELL - 2 * offset,
+                thin,
+            );
+        }
+        for (row, left, right) in &self.links {
+            let (x, y) = (CELL * (2 * left + 1) + offset, CELL * 2 * row + offset);
+            rect(x, y, CELL * 2 * (right - left) + thin, thin);
+        }
+        for (col, top, bottom) in &self.vars {
+            let (x, y) = (CELL * (2 * col + 1) + offset, CELL * 2 * top + offset);
+            rect(x, y, thin, CELL * 2 * (bottom - top) + thin);
+        }
+    }
+
+    /// The diagram as a self-contained SVG image.
+    pub fn to_svg(&self) -> String {
+        let (width, height) = self.svg_size();
+        let mut out = svg_header(width, height);
+        self.svg_shapes("  ", &mut out);
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+fn svg_header(width: usize, height: usize) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    )
+}
+
+fn horizontal(cells: &mut [Vec<[bool; 4]>], row: usize, from: usize, to: usize) {
+    for col in from..to {
+        cells[row][col][3] = true;
+        cells[row][col + 1][2] = true;
+    }
+}
+
+fn box_char([up, down, left, right]: [bool; 4]) -> char {
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (true, true, false, false) => '│',
+        (false, false, true, true) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+impl<T: IdentType> Trace<T> {
+    /// The diagram of every step of the reduction.
+    pub fn diagrams(&self) -> Vec<Diagram> {
+        self.steps.iter().map(Diagram::new).collect()
+    }
+
+    /// An SVG animation showing each step in turn for a second, looping forever.
+    pub fn to_animated_svg(&self) -> String {
+        let diagrams = self.diagrams();
+        let (width, height) = diagrams
+            .iter()
+            .map(Diagram::svg_size)
+            .fold((0, 0), |(w1, h1), (w2, h2)| (w1.max(w2), h1.max(h2)));
+        let frames = diagrams.len();
+        let key_times: Vec<_> = (0..frames)
+            .map(|i| (i as f64 / frames as f64).to_string())
+            .collect();
+        let mut out = svg_header(width, height);
+        for (i, diagram) in diagrams.iter().enumerate() {
+            let values: Vec<_> = (0..frames)
+                .map(|j| if i == j { "visible" } else { "hidden" })
+                .collect();
+            out.push_str("  <g visibility=\"hidden\">\n");
+            writeln!(
+                out,
+                "    <animate attributeName=\"visibility\" values=\"{}\" keyTimes=\"{}\" \
+                 calcMode=\"discrete\" dur=\"{}s\" repeatCount=\"indefinite\"/>",
+                values.join(";"),
+                key_times.join(";"),
+                frames as f64 * FRAME_SECONDS
+            )
+            .unwrap();
+            diagram.svg_shapes("    ", &mut out);
+            out.push_str("  </g>\n");
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
@@ -0,0 +1,323 @@
+//! Formatting of source files. Declarations are printed from their concrete syntax tree rather
+//! than from the parsed terms, so literals, `∀`, binder groups and parentheses stay as written,
+//! only ASCII symbols becoming their Unicode forms.
+//!
+//! Comments and blank lines between declarations are kept. A declaration with a comment inside
+//! is left as it is, as the tree doesn't say where the comment goes.
+
+use pest::error::Error;
+use pest::iterators::Pair;
+use pest::Parser;
+
+use crate::parser::{LambdaParser, Rule};
+use crate::pretty::Doc::{self, *};
+
+fn owned(pair: &Pair<Rule>) -> Doc<'static> {
+    Owned(pair.as_str().to_string())
+}
+
+/// The term of an expression which is a single term without arguments, like the body of `λx:*.λy:*.x`.
+fn single_term<'i>(expr: &Pair<'i, Rule>) -> Option<Pair<'i, Rule>> {
+    let mut pairs = expr.clone().into_inner();
+    let spine = pairs.next()?;
+    if pairs.next().is_some() {
+        return None;
+    }
+    let mut terms = spine.into_inner();
+    let term = terms.next()?;
+    match terms.next() {
+        Some(_) => None,
+        None => term.into_inner().next(),
+    }
+}
+
+fn expr(pair: Pair<Rule>) -> Doc<'static> {
+    let mut pairs = pair.into_inner();
+    let spine = spine(pairs.next().unwrap());
+    match pairs.next() {
+        Some(rhs) => Doc::group(Concat(vec![spine, Text(&" →"), Line, expr(rhs)])),
+        None => spine,
+    }
+}
+
+fn spine(pair: Pair<Rule>) -> Doc<'static> {
+    let mut terms = pair
+        .into_inner()
+        .map(|pair| term(pair.into_inner().next().unwrap()));
+    let head = terms.next().unwrap();
+    let args: Vec<_> = terms.flat_map(|arg| vec![Line, arg]).collect();
+    if args.is_empty() {
+        head
+    } else {
+        Doc::group(Concat(vec![head, Doc::nest(2, Concat(args))]))
+    }
+}
+
+/// `x y:T`, or `x y` for the bare binders of `∀`.
+fn binders(pair: Pair<Rule>) -> Doc<'static> {
+    let rule = pair.as_rule();
+    let mut pairs: Vec<_> = pair.into_inner().collect();
+    let ty = match rule {
+        Rule::typed_binders => Some(expr(pairs.pop().unwrap())),
+        _ => None,
+    };
+    let idents: Vec<_> = pairs.iter().map(|pair| pair.as_str()).collect();
+    let mut docs = vec![Owned(idents.join(" "))];
+    if let Some(ty) = ty {
+        docs.extend(vec![Text(&":"), ty]);
+    }
+    Concat(docs)
+}
+
+/// The binders of an abstraction or a product, and its body.
+fn telescope(pair: Pair<Rule>) -> (Doc<'static>, Pair<Rule>) {
+    // Binder groups are silent in the tree, only the source tells `λ(x:A).x` from `λx:A.x`.
+    let grouped = pair
+        .as_str()
+        .trim_start_matches(|c| c != '(' && c != ':' && c != ',')
+        .starts_with('(');
+    let mut pairs: Vec<_> = pair.into_inner().collect();
+    let body = pairs.pop().unwrap();
+    let mut docs = vec![];
+    for (i, pair) in pairs.into_iter().enumerate() {
+        if i > 0 {
+            docs.push(Text(&" "));
+        }
+        if grouped {
+            docs.extend(vec![Text(&"("), binders(pair), Text(&")")]);
+        } else {
+            docs.push(binders(pair));
+        }
+    }
+    (Concat(docs), body)
+}
+
+fn term(pair: Pair<Rule>) -> Doc<'static> {
+    match pair.as_rule() {
+        Rule::var | Rule::nat | Rule::boolean => owned(&pair),
+        Rule::kind if pair.as_str() == "*" => Text(&"*"),
+        Rule::kind => Text(&"□"),
+        Rule::app => Concat(vec![
+            Text(&"("),
+            Doc::nest(1, expr(pair.into_inner().next().unwrap())),
+            Text(&")"),
+        ]),
+        Rule::list => {
+            let mut docs = vec![Text(&"[")];
+            let mut elems = vec![];
+            for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::expr => elems.push(expr(pair)),
+                    Rule::list_ty => {
+                        let ty = expr(pair.into_inner().next().unwrap());
+                        let sep = match elems.is_empty() {
+                            true => Concat(vec![Text(&":"), Softline]),
+                            false => Concat(vec![Text(&" :"), Line]),
+                        };
+                        elems.push(Concat(vec![sep, ty]));
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            let mut inner = vec![];
+            let count = elems.len();
+            for (i, elem) in elems.into_iter().enumerate() {
+                inner.push(elem);
+                // The type comes last and carries its own separator.
+                if i + 2 < count {
+                    inner.extend(vec![Text(&","), Line]);
+                }
+            }
+            docs.push(Doc::nest(1, Concat(inner)));
+            docs.push(Text(&"]"));
+            Doc::group(Concat(docs))
+        }
+        Rule::abs | Rule::pi => {
+            let (symbol, rule) = match pair.as_rule() {
+                Rule::abs => (&"λ", Rule::abs),
+                _ => (&"π", Rule::pi),
+            };
+            let mut docs = vec![];
+            let mut pair = pair;
+            loop {
+                let (binders, body) = telescope(pair);
+                docs.extend(vec![Text(symbol), binders, Text(&".")]);
+                match single_term(&body) {
+                    Some(inner) if inner.as_rule() == rule => pair = inner,
+                    _ => {
+                        docs.push(Doc::nest(2, Concat(vec![Softline, expr(body)])));
+                        break;
+                    }
+                }
+            }
+            Doc::group(Concat(docs))
+        }
+        Rule::forall_pi => {
+            let symbol = if pair.as_str().starts_with('Π') {
+                &"Π"
+            } else {
+                &"∀"
+            };
+            let (binders, body) = telescope(pair);
+            Doc::group(Concat(vec![
+                Text(symbol),
+                binders,
+                Text(&","),
+                Doc::nest(2, Concat(vec![Line, expr(body)])),
+            ]))
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn decl(pair: Pair<Rule>) -> Doc<'static> {
+    let rule = pair.as_rule();
+    let mut pairs = pair.into_inner();
+    let indented = |docs: Vec<Doc<'static>>| Doc::nest(2, Concat(docs));
+    match rule {
+        Rule::def => {
+            let ident = pairs.next().unwrap().as_str();
+            let mut exprs: Vec<_> = pairs.collect();
+            let body = expr(exprs.pop().unwrap());
+            let mut docs = vec![Owned(format!("def {}", ident))];
+            if let Some(ty) = exprs.pop() {
+                docs.extend(vec![Text(&" : "), expr(ty)]);
+            }
+            docs.extend(vec![Text(&" :="), indented(vec![Line, body])]);
+            Doc::group(Concat(docs))
+        }
+        Rule::axiom => {
+            let ident = pairs.next().unwrap().as_str();
+            let ty = expr(pairs.next().unwrap());
+            Doc::group(Concat(vec![
+                Owned(format!("axiom {} :", ident)),
+                indented(vec![Line, ty]),
+            ]))
+        }
+        Rule::eval => {
+            let mut docs = vec![Line, expr(pairs.next().unwrap())];
+            if let Some(strategy) = pairs.next() {
+                docs.extend(vec![Line, Owned(format!("with {}", strategy.as_str()))]);
+            }
+            Doc::group(Concat(vec![Text(&"#eval"), indented(docs)]))
+        }
+        Rule::check => Doc::group(Concat(vec![
+            Text(&"#check"),
+            indented(vec![Line, expr(pairs.next().unwrap())]),
+        ])),
+        Rule::assert => Doc::group(Concat(vec![
+            Text(&"#assert"),
+            indented(vec![
+                Line,
+                expr(pairs.next().unwrap()),
+                Line,
+                Text(&"== "),
+                expr(pairs.next().unwrap()),
+            ]),
+        ])),
+        _ => unreachable!(),
+    }
+}
+
+fn at_line_start(out: &str) -> bool {
+    out.is_empty() || out.ends_with('\n')
+}
+
+/// The length of the block comment at the start of `text`, nested comments included.
+fn block_comment(text: &str) -> usize {
+    let mut depth = 0;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if text[i..].starts_with("{-") {
+            depth += 1;
+            chars.next();
+        } else if text[i..].starts_with("-}") {
+            depth -= 1;
+            chars.next();
+            if depth == 0 {
+                return i + 2;
+            }
+        } else if depth == 0 {
+            return i + c.len_utf8();
+        }
+    }
+    text.len()
+}
+
+/// The length of the comment or whitespace at the start of `text`, if there is one.
+fn trivia_len(text: &str) -> Option<usize> {
+    if text.is_empty() {
+        None
+    } else if text.starts_with("--") {
+        Some(text.find('\n').unwrap_or(text.len()))
+    } else if text.starts_with("{-") {
+        Some(block_comment(text))
+    } else {
+        match text.find(|c: char| !c.is_whitespace()) {
+            Some(0) => None,
+            len => Some(len.unwrap_or(text.len())),
+        }
+    }
+}
+
+/// The length of `text` without the comments and whitespace at its end, which pest includes
+/// in the spans of rules ending with a repetition.
+fn content_len(text: &str) -> usize {
+    let (mut i, mut len) = (0, 0);
+    while i < text.len() {
+        match trivia_len(&text[i..]) {
+            Some(trivia) => i += trivia,
+            None => {
+                i += text[i..].chars().next().unwrap().len_utf8();
+                len = i;
+            }
+        }
+    }
+    len
+}
+
+/// Copy the comments of the whitespace between declarations, keeping at most one blank line.
+fn trivia(mut text: &str, out: &mut String) {
+    while let Some(len) = trivia_len(text) {
+        let (trivia, rest) = text.split_at(len);
+        if trivia.starts_with("--") || trivia.starts_with("{-") {
+            if !at_line_start(out) {
+                out.push(' ');
+            }
+            out.push_str(trivia);
+        } else if !out.is_empty() {
+            match trivia.matches('\n').count() {
+                0 => {}
+                1 => out.push('\n'),
+                _ => out.push_str("\n\n"),
+            }
+        }
+        text = rest;
+    }
+}
+
+/// Lay out every declaration of `source` within `width` columns where possible.
+pub fn format_module(source: &str, width: usize) -> Result<String, Error<Rule>> {
+    let module = LambdaParser::parse(Rule::module, source)?.next().unwrap();
+    let mut out = String::new();
+    let mut end = 0;
+    for pair in module.into_inner() {
+        if pair.as_rule() == Rule::EOI {
+            break;
+        }
+        let start = pair.as_span().start();
+        trivia(&source[end..start], &mut out);
+        let text = &pair.as_str()[..content_len(pair.as_str())];
+        end = start + text.len();
+        if !at_line_start(&out) {
+            out.push('\n');
+        }
+        if text.contains("--") || text.contains("{-") {
+            out.push_str(text);
+        } else {
+            decl(pair).render(width, &mut out).unwrap();
+        }
+    }
+    trivia(&source[end..], &mut out);
+    Ok(out.trim_end().to_string())
+}
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use crate::ast::Fresh;
+use crate::index::bare::BareIdent;
+
+/// Policy for renaming a binder whose name is already taken.
+pub trait NameScheme {
+    /// The next candidate after `name`. Repeatedly applying it must never revisit a name.
+    fn next(&self, name: &str) -> BareIdent;
+
+    /// Names that must never be chosen, even when nothing would be captured.
+    fn is_reserved(&self, _name: &str) -> bool {
+        false
+    }
+}
+
+/// Split `name` into its stem and the number encoded by its trailing `digits`.
+fn split_suffix<'a>(name: &'a str, digits: &[char]) -> (&'a str, Option<usize>) {
+    let stem = name.trim_end_matches(|chr| digits.contains(&chr));
+    let suffix = name[stem.len()..].chars().fold(None, |n, chr| {
+        let digit = digits.iter().position(|d| *d == chr).unwrap();
+        Some(n.unwrap_or(0) * 10 + digit)
+    });
+    (stem, suffix)
+}
+
+fn with_suffix(stem: &str, n: usize, digits: &[char]) -> BareIdent {
+    let suffix: String = n
+        .to_string()
+        .chars()
+        .map(|chr| digits[chr.to_digit(10).unwrap() as usize])
+        .collect();
+    format!("{}{}", stem, suffix)
+}
+
+const ASCII_DIGITS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+const SUBSCRIPT_DIGITS: [char; 10] = ['₀', '₁', '₂', '₃', '₄', '₅', '₆', '₇', '₈', '₉'];
+
+/// `x`, `x1`, `x2`, ...
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Numeric;
+
+impl NameScheme for Numeric {
+    fn next(&self, name: &str) -> BareIdent {
+        name.to_string().fresh()
+    }
+}
+
+/// `x`, `x'`, `x''`, ...
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Primes;
+
+impl NameScheme for Primes {
+    fn next(&self, name: &str) -> BareIdent {
+        format!("{}'", name)
+    }
+}
+
+/// `x`, `x₁`, `x₂`, ...
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Subscripts;
+
+impl NameScheme for Subscripts {
+    fn next(&self, name: &str) -> BareIdent {
+        let (stem, n) = split_suffix(name, &SUBSCRIPT_DIGITS);
+        with_suffix(stem, n.unwrap_or(0) + 1, &SUBSCRIPT_DIGITS)
+    }
+}
+
+/// Cycle through a fixed alphabet, numbering each further round: with `x y z`,
+/// `x`, `y`, `z`, `x1`, `y1`, `z1`, `x2`, ...
+///
+/// Names outside of the alphabet continue from its first letter.
+#[derive(Clone, Debug)]
+pub struct Alphabet {
+    letters: Vec<BareIdent>,
+}
+
+impl Alphabet {
+    pub fn new(letters: Vec<BareIdent>) -> Self {
+        assert!(!letters.is_empty(), "alphabet must not be empty.");
+        Alphabet { letters }
+    }
+}
+
+impl NameScheme for Alphabet {
+    fn next(&self, name: &str) -> BareIdent {
+        let (stem, n) = split_suffix(name, &ASCII_DIGITS);
+        let n = n.unwrap_or(0);
+        let (letter, round) = match self.letters.iter().position(|letter| letter == stem) {
+            Some(i) if i + 1 < self.letters.len() => (&self.letters[i + 1], n),
+            Some(_) => (&self.letters[0], n + 1),
+            None => (&self.letters[0], 0),
+        };
+        if round == 0 {
+            letter.clone()
+        } else {
+            with_suffix(letter, round, &ASCII_DIGITS)
+        }
+    }
+}
+
+/// Wrap another scheme, skipping over a set of reserved names.
+#[derive(Clone, Debug)]
+pub struct Avoiding<S: NameScheme> {
+    scheme: S,
+    reserved: HashSet<BareIdent>,
+}
+
+impl<S: NameScheme> Avoiding<S> {
+    pub fn new(scheme: S, reserved: HashSet<BareIdent>) -> Self {
+        Avoiding { scheme, reserved }
+    }
+}
+
+impl<S: NameScheme> NameScheme for Avoiding<S> {
+    fn next(&self, name: &str) -> BareIdent {
+        self.scheme.next(name)
+    }
+
+    fn is_reserved(&self, name: &str) -> bool {
+        self.reserved.contains(name) || self.scheme.is_reserved(name)
+    }
+}
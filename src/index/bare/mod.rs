@@ -1,22 +1,26 @@
 use crate::ast::{Fresh, IdentType};
 
+pub mod fresh;
+
 pub type BareIdent = String;
 
 impl IdentType for BareIdent {}
 
+/// Bump the trailing ASCII number of the name, `x'` becomes `x'1` and `x₁9` becomes `x₁10`.
+///
+/// Identifiers may end in any digits or primes, so the result is a valid identifier whenever
+/// the name is one, or is `_`.
 impl Fresh for BareIdent {
     fn fresh(&self) -> Self {
-        let chars = self.chars();
-        let initial_offset = chars.clone().count();
-        let (suffix, offset, _) = chars.rfold((0, initial_offset, 0), |(i, offset, e), chr| {
-            chr.to_digit(10)
-                .and_then(|n| Some((n * u32::pow(10, e) + i, offset - 1, e + 1)))
-                .unwrap_or((i, offset, e))
-        });
-        format!(
-            "{}{}",
-            self.chars().take(offset).collect::<String>(),
-            suffix + 1
-        )
+        let prefix = self.trim_end_matches(|c: char| c.is_ascii_digit());
+        match self[prefix.len()..]
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_add(1))
+        {
+            Some(n) => format!("{}{}", prefix, n),
+            // No number, or one too large to bump.
+            None => format!("{}1", self),
+        }
     }
 }
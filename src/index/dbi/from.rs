@@ -0,0 +1,41 @@
+use crate::ast::*;
+use crate::index::dbi::DBI;
+
+impl<T: IdentType> From<Term<T>> for Term<DBI<T>> {
+    fn from(term: Term<T>) -> Self {
+        term.to_dbi()
+    }
+}
+
+impl<T: IdentType> Term<T> {
+    pub fn to_dbi(&self) -> Term<DBI<T>> {
+        _to_dbi(self, &mut vec![])
+    }
+}
+
+fn _to_dbi<'a, T: IdentType>(term: &'a Term<T>, binders: &mut Vec<&'a T>) -> Term<DBI<T>> {
+    match term {
+        Var(x) => Var(
+            match binders.iter().rev().position(|bound_var| *bound_var == x) {
+                Some(i) => DBI::Bound(i, x.clone()),
+                None => DBI::Free(x.clone()),
+            },
+        ),
+        App(e1, e2) => App(box _to_dbi(e1, binders), box _to_dbi(e2, binders)),
+        Abs(x, ty, e) => {
+            let ty = _to_dbi(ty, binders);
+            binders.push(x);
+            let e = _to_dbi(e, binders);
+            binders.pop();
+            Abs(DBI::Binder(x.clone()), box ty, box e)
+        }
+        Pi(x, lty, rty) => {
+            let lty = _to_dbi(lty, binders);
+            binders.push(x);
+            let rty = _to_dbi(rty, binders);
+            binders.pop();
+            Pi(DBI::Binder(x.clone()), box lty, box rty)
+        }
+        Kind(kind) => Kind(*kind),
+    }
+}
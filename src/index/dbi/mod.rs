@@ -0,0 +1,118 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use crate::ast::*;
+
+mod from;
+
+/// De Bruijn indexed identifier.
+///
+/// Binders are anonymous and occurrences of bound variables only remember how many binders lie
+/// between them and their binding site, so alpha-equivalent terms have equal De Bruijn views.
+/// Original identifiers are kept around for display only.
+#[derive(Clone, Debug)]
+pub enum DBI<T: IdentType> {
+    Binder(T),
+    Bound(usize, T),
+    Free(T),
+}
+
+impl<T: IdentType> PartialEq for DBI<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DBI::Binder(_), DBI::Binder(_)) => true,
+            (DBI::Bound(i, _), DBI::Bound(j, _)) => i == j,
+            (DBI::Free(x), DBI::Free(y)) => x == y,
+            _ => false,
+        }
+    }
+}
+
+impl<T: IdentType> Eq for DBI<T> {}
+
+impl<T: IdentType> Hash for DBI<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            DBI::Binder(_) => 0.hash(state),
+            DBI::Bound(i, _) => {
+                1.hash(state);
+                i.hash(state)
+            }
+            DBI::Free(x) => {
+                2.hash(state);
+                x.hash(state)
+            }
+        }
+    }
+}
+
+impl<T: IdentType> Display for DBI<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DBI::Binder(x) | DBI::Free(x) => write!(f, "{}", x),
+            DBI::Bound(i, _) => write!(f, "{}", i),
+        }
+    }
+}
+
+impl<T: IdentType> IdentType for DBI<T> {}
+
+impl<T: IdentType> Term<T> {
+    /// Alpha-equivalence: equality up to renaming of bound variables, annotations included.
+    pub fn alpha_eq(&self, other: &Self) -> bool {
+        self.to_dbi() == other.to_dbi()
+    }
+
+    /// A hash that is invariant under alpha-renaming.
+    pub fn alpha_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.to_dbi().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A term that is compared and hashed up to alpha-equivalence, so it can be used as a
+/// `HashMap`/`HashSet` key for memoization and cycle detection.
+#[derive(Clone, Debug)]
+pub struct AlphaTerm<T: IdentType> {
+    term: Term<T>,
+    dbi: Term<DBI<T>>,
+}
+
+impl<T: IdentType> AlphaTerm<T> {
+    pub fn new(term: Term<T>) -> Self {
+        let dbi = term.to_dbi();
+        AlphaTerm { term, dbi }
+    }
+
+    pub fn term(&self) -> &Term<T> {
+        &self.term
+    }
+}
+
+impl<T: IdentType> From<Term<T>> for AlphaTerm<T> {
+    fn from(term: Term<T>) -> Self {
+        AlphaTerm::new(term)
+    }
+}
+
+impl<T: IdentType> From<AlphaTerm<T>> for Term<T> {
+    fn from(term: AlphaTerm<T>) -> Self {
+        term.term
+    }
+}
+
+impl<T: IdentType> PartialEq for AlphaTerm<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dbi == other.dbi
+    }
+}
+
+impl<T: IdentType> Eq for AlphaTerm<T> {}
+
+impl<T: IdentType> Hash for AlphaTerm<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dbi.hash(state)
+    }
+}
@@ -1,2 +1,3 @@
 pub mod bare;
+pub mod dbi;
 pub mod uid;
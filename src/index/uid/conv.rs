@@ -0,0 +1,127 @@
+use crate::ast::*;
+use crate::index::uid::reduce::DEFAULT_LIMIT;
+use crate::index::uid::{fresh_uid, UID};
+
+/// Outcome of a beta-eta conversion check.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Conversion {
+    Equal,
+    NotEqual,
+    /// The step limit ran out before the terms could be told apart.
+    Unknown(usize),
+}
+
+struct OutOfFuel;
+
+struct Checker {
+    fuel: usize,
+    lhs_binders: Vec<usize>,
+    rhs_binders: Vec<usize>,
+}
+
+impl Checker {
+    fn whnf(&mut self, term: &Term<UID>) -> Result<Term<UID>, OutOfFuel> {
+        match term {
+            App(e1, e2) => match self.whnf(e1)? {
+                Abs(x, ty, e) => {
+                    if self.fuel == 0 {
+                        return Err(OutOfFuel);
+                    }
+                    self.fuel -= 1;
+                    self.whnf(&Abs(x, ty, e).subst(e2))
+                }
+                e1_ => Ok(App(box e1_, e2.clone())),
+            },
+            _ => Ok(term.clone()),
+        }
+    }
+
+    /// A variable that occurs in neither side, nor in any binder we are under.
+    fn fresh(x: &UID) -> UID {
+        UID {
+            name: x.name.clone(),
+            uid: fresh_uid(),
+        }
+    }
+
+    fn under<F>(&mut self, x: &UID, y: &UID, f: F) -> Result<bool, OutOfFuel>
+    where
+        F: FnOnce(&mut Self) -> Result<bool, OutOfFuel>,
+    {
+        self.lhs_binders.push(x.uid);
+        self.rhs_binders.push(y.uid);
+        let rtn = f(self);
+        self.lhs_binders.pop();
+        self.rhs_binders.pop();
+        rtn
+    }
+
+    fn var_eq(&self, x: &UID, y: &UID) -> bool {
+        match (
+            self.lhs_binders.iter().rposition(|uid| *uid == x.uid),
+            self.rhs_binders.iter().rposition(|uid| *uid == y.uid),
+        ) {
+            (Some(i), Some(j)) => i == j,
+            // Free variables are compared by name, so terms built by separate parses can be compared.
+            (None, None) => x.name == y.name,
+            _ => false,
+        }
+    }
+
+    fn conv(&mut self, lhs: &Term<UID>, rhs: &Term<UID>) -> Result<bool, OutOfFuel> {
+        let (lhs, rhs) = (self.whnf(lhs)?, self.whnf(rhs)?);
+        match (&lhs, &rhs) {
+            (Kind(k1), Kind(k2)) => Ok(k1 == k2),
+            // Domain annotations of abstractions are not compared, as eta can't recover them anyway.
+            (Abs(x, _, e1), Abs(y, _, e2)) => self.under(x, y, |s| s.conv(e1, e2)),
+            (Abs(x, _, e), _) => {
+                let z = Self::fresh(x);
+                self.under(x, &z, |s| {
+                    s.conv(e, &App(box rhs.clone(), box Var(z.clone())))
+                })
+            }
+            (_, Abs(y, _, e)) => {
+                let z = Self::fresh(y);
+                self.under(&z, y, |s| {
+                    s.conv(&App(box lhs.clone(), box Var(z.clone())), e)
+                })
+            }
+            (Pi(x, lty1, rty1), Pi(y, lty2, rty2)) => {
+                Ok(self.conv(lty1, lty2)? && self.under(x, y, |s| s.conv(rty1, rty2))?)
+            }
+            (Var(_), Var(_)) | (App(_, _), App(_, _)) => self.conv_neutral(&lhs, &rhs),
+            _ => Ok(false),
+        }
+    }
+
+    /// Compare two application spines in weak head normal form, arguments are normalized lazily.
+    fn conv_neutral(&mut self, lhs: &Term<UID>, rhs: &Term<UID>) -> Result<bool, OutOfFuel> {
+        match (lhs, rhs) {
+            (Var(x), Var(y)) => Ok(self.var_eq(x, y)),
+            (App(f1, e1), App(f2, e2)) => Ok(self.conv_neutral(f1, f2)? && self.conv(e1, e2)?),
+            (Var(_), _) | (_, Var(_)) | (App(_, _), _) | (_, App(_, _)) => Ok(false),
+            _ => self.conv(lhs, rhs),
+        }
+    }
+}
+
+impl Term<UID> {
+    /// Decide beta-eta convertibility by comparing weak head normal forms, descending into
+    /// binders and application spines only as far as needed.
+    ///
+    /// `limit` bounds the total number of beta steps, and defaults to the same limit as
+    /// `beta_reduce`.
+    pub fn convertible(&self, other: &Term<UID>, limit: Option<usize>) -> Conversion {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        let mut checker = Checker {
+            fuel: limit,
+            lhs_binders: vec![],
+            rhs_binders: vec![],
+        };
+        match checker.conv(self, other) {
+            Ok(true) => Conversion::Equal,
+            Ok(false) => Conversion::NotEqual,
+            Err(OutOfFuel) => Conversion::Unknown(limit),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
+use crate::ast::Term;
 use crate::ast::Term::*;
-use crate::ast::{Kinds, Term};
 use crate::index::bare::BareIdent;
 use crate::index::uid::{UIDGenerator, UID};
 
@@ -28,10 +28,10 @@ fn _from_bare(
             (App(box lhs, box rhs), free_vars)
         }
         Var(x) => {
-            let uid = if free_vars.contains_key(x) {
-                free_vars[x]
-            } else if bound_vars.contains_key(x) {
+            let uid = if bound_vars.contains_key(x) {
                 bound_vars[x]
+            } else if free_vars.contains_key(x) {
+                free_vars[x]
             } else {
                 free_vars.insert(x.clone(), uid_generator.next());
                 free_vars[x]
@@ -44,7 +44,8 @@ fn _from_bare(
                 free_vars,
             )
         }
-        Abs(x, _, e) => {
+        Abs(x, ty, e) => {
+            let (ty, free_vars) = _from_bare(ty, uid_generator, free_vars, bound_vars.clone());
             let bound_id = uid_generator.next();
             bound_vars.insert(x.clone(), bound_id);
             let (term, free_vars) = _from_bare(e, uid_generator, free_vars, bound_vars);
@@ -54,13 +55,29 @@ fn _from_bare(
                         name: x.clone(),
                         uid: bound_id,
                     },
-                    box Term::Kind(Kinds::Star), // TODO
+                    box ty,
                     box term,
                 ),
                 free_vars,
             )
         }
-        Pi(_, _, _) => unimplemented!(), // TODO
+        Pi(x, lty, rty) => {
+            let (lty, free_vars) = _from_bare(lty, uid_generator, free_vars, bound_vars.clone());
+            let bound_id = uid_generator.next();
+            bound_vars.insert(x.clone(), bound_id);
+            let (rty, free_vars) = _from_bare(rty, uid_generator, free_vars, bound_vars);
+            (
+                Pi(
+                    UID {
+                        name: x.clone(),
+                        uid: bound_id,
+                    },
+                    box lty,
+                    box rty,
+                ),
+                free_vars,
+            )
+        }
         Kind(kind) => (Term::Kind(*kind), free_vars),
     }
 }
@@ -1,48 +1,87 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::ast::Term;
 use crate::ast::Term::*;
+use crate::ast::{Term, Trace};
+use crate::index::bare::fresh::{NameScheme, Numeric};
 use crate::index::bare::BareIdent;
 use crate::index::uid::UID;
 
 impl From<Term<UID>> for Term<BareIdent> {
     fn from(term: Term<UID>) -> Self {
-        fn _to_bare(
-            term: Term<UID>,
-            var_maps: &mut HashMap<UID, String>,
-            var_set: &mut HashSet<BareIdent>,
-        ) -> Term<BareIdent> {
-            match term {
-                Var(x) => Var(if !var_maps.contains_key(&x) {
-                    x.name
-                } else {
-                    var_maps[&x].clone()
-                }),
-                App(e1, e2) => App(
-                    box _to_bare(*e1, var_maps, var_set),
-                    box _to_bare(*e2, var_maps, var_set),
-                ),
-                Abs(x, ty, e) => {
-                    // TODO avoid unnecessary renames (eg. λx1.λx.x1 x instead of λx1.λx2.x1 x2)
-                    let bound_name = if e.has_name_collision(&x) {
-                        let next_fv_name = e.new_fv_name(&x.name, var_set);
-                        var_maps.insert(x, next_fv_name.clone());
-                        var_set.insert(next_fv_name.clone());
-                        next_fv_name
-                    } else {
-                        x.name
-                    };
-                    // TODO ty renaming
-                    Abs(
-                        bound_name,
-                        box _to_bare(*ty, var_maps, var_set),
-                        box _to_bare(*e, var_maps, var_set),
-                    )
-                }
-                Pi(_, _, _) => unimplemented!(),
-                Kind(kind) => Term::Kind(kind),
-            }
+        term.to_bare_with(&Numeric)
+    }
+}
+
+impl From<Trace<UID>> for Trace<BareIdent> {
+    fn from(trace: Trace<UID>) -> Self {
+        Trace {
+            strategy: trace.strategy,
+            steps: trace.steps.into_iter().map(Term::from).collect(),
+            normal: trace.normal,
         }
-        _to_bare(term, &mut HashMap::default(), &mut HashSet::default())
     }
 }
+
+impl Term<UID> {
+    /// Recover a named term, renaming captured binders according to `scheme`.
+    pub fn to_bare_with(&self, scheme: &dyn NameScheme) -> Term<BareIdent> {
+        to_bare(self, &mut HashMap::default(), scheme)
+    }
+}
+
+fn name_of(x: &UID, names: &HashMap<usize, BareIdent>) -> BareIdent {
+    names.get(&x.uid).unwrap_or(&x.name).clone()
+}
+
+fn to_bare(
+    term: &Term<UID>,
+    names: &mut HashMap<usize, BareIdent>,
+    scheme: &dyn NameScheme,
+) -> Term<BareIdent> {
+    match term {
+        Var(x) => Var(name_of(x, names)),
+        App(e1, e2) => App(
+            box to_bare(e1, names, scheme),
+            box to_bare(e2, names, scheme),
+        ),
+        Abs(x, ty, e) => {
+            let ty = to_bare(ty, names, scheme);
+            let (x, e) = bind(x, e, names, scheme);
+            Abs(x, box ty, box e)
+        }
+        Pi(x, lty, rty) => {
+            let lty = to_bare(lty, names, scheme);
+            let (x, rty) = bind(x, rty, names, scheme);
+            Pi(x, box lty, box rty)
+        }
+        Kind(kind) => Kind(*kind),
+    }
+}
+
+/// Name the binder `x` of `body`, keeping its original name unless that would capture
+/// another variable occurring free in `body`, or `scheme` reserves it.
+fn bind(
+    x: &UID,
+    body: &Term<UID>,
+    names: &mut HashMap<usize, BareIdent>,
+    scheme: &dyn NameScheme,
+) -> (BareIdent, Term<BareIdent>) {
+    let taken: HashSet<BareIdent> = body
+        .fv()
+        .into_iter()
+        .filter(|v| v.uid != x.uid)
+        .map(|v| name_of(v, names))
+        .collect();
+    let mut name = x.name.clone();
+    while taken.contains(&name) || scheme.is_reserved(&name) {
+        name = scheme.next(&name);
+    }
+
+    let shadowed = names.insert(x.uid, name.clone());
+    let body = to_bare(body, names, scheme);
+    match shadowed {
+        Some(shadowed) => names.insert(x.uid, shadowed),
+        None => names.remove(&x.uid),
+    };
+    (name, body)
+}
@@ -1,13 +1,22 @@
-use std::cmp::max;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::ast::*;
 use crate::index::bare::BareIdent;
 
+pub use conv::Conversion;
+pub(crate) use reduce::DEFAULT_LIMIT;
+pub use typing::TypeError;
+
+mod conv;
 pub(crate) mod from;
 mod into;
 mod reduce;
+mod typing;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct UIDGenerator {
@@ -20,16 +29,49 @@ impl UIDGenerator {
     }
     pub fn next(&mut self) -> usize {
         self.count += 1;
+        LAST_UID.fetch_max(self.count, Ordering::Relaxed);
         self.count
     }
 }
 
+/// The highest uid handed out so far, by any generator.
+static LAST_UID: AtomicUsize = AtomicUsize::new(0);
+
+/// A uid distinct from every uid handed out so far, for binders introduced by substitution.
+pub fn fresh_uid() -> usize {
+    LAST_UID.fetch_add(1, Ordering::Relaxed) + 1
+}
+
 #[derive(Clone, Debug, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(from = "RawUID")
+)]
 pub struct UID {
     pub name: String,
     pub uid: usize,
 }
 
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawUID {
+    name: String,
+    uid: usize,
+}
+
+/// Deserialized uids are taken into account by `fresh_uid`, so fresh binders never collide with them.
+#[cfg(feature = "serde")]
+impl From<RawUID> for UID {
+    fn from(raw: RawUID) -> Self {
+        LAST_UID.fetch_max(raw.uid, Ordering::Relaxed);
+        UID {
+            name: raw.name,
+            uid: raw.uid,
+        }
+    }
+}
+
 impl PartialEq for UID {
     fn eq(&self, other: &Self) -> bool {
         self.uid == other.uid
@@ -47,32 +89,76 @@ impl Display for UID {
 impl IdentType for UID {}
 
 impl Term<UID> {
-    pub fn new_fv_name(&self, old_name: &BareIdent, var_set: &HashSet<BareIdent>) -> BareIdent {
-        let free_var_names = self.fv_names();
-        let mut name = old_name.clone();
-        while free_var_names.contains(&name) || var_set.contains(&name) {
-            name = name.fresh();
-        }
-        name
-    }
-
-    pub fn fv_names(&self) -> HashSet<BareIdent> {
-        self.fv().iter().map(|ident| ident.name.clone()).collect()
+    /// Copy the term, giving every binder a fresh uid.
+    pub fn freshen(&self) -> Term<UID> {
+        _freshen(self, &mut HashMap::default())
     }
 
-    pub fn uid_generator(&self) -> UIDGenerator {
-        UIDGenerator {
-            count: self.fv().iter().fold(0usize, |count, v| max(count, v.uid)),
+    /// Replace each free variable named in `defs` by a fresh copy of its definition.
+    pub fn unfold(&self, defs: &HashMap<BareIdent, Term<UID>>) -> Term<UID> {
+        fn _unfold(
+            term: &Term<UID>,
+            fvs: &HashSet<&UID>,
+            defs: &HashMap<BareIdent, Term<UID>>,
+        ) -> Term<UID> {
+            match term {
+                Var(x) if fvs.contains(x) && defs.contains_key(&x.name) => defs[&x.name].freshen(),
+                Var(_) | Kind(_) => term.clone(),
+                App(e1, e2) => App(box _unfold(e1, fvs, defs), box _unfold(e2, fvs, defs)),
+                Abs(x, ty, e) => Abs(
+                    x.clone(),
+                    box _unfold(ty, fvs, defs),
+                    box _unfold(e, fvs, defs),
+                ),
+                Pi(x, lty, rty) => Pi(
+                    x.clone(),
+                    box _unfold(lty, fvs, defs),
+                    box _unfold(rty, fvs, defs),
+                ),
+            }
         }
+        _unfold(self, &self.fv(), defs)
     }
+}
 
-    pub fn has_name_collision(&self, bound_var: &UID) -> bool {
-        match self {
-            Var(x) => x != bound_var && x.name == bound_var.name,
-            Abs(bound_var, _, e) => e.has_name_collision(bound_var),
-            App(e1, e2) => e1.has_name_collision(bound_var) || e2.has_name_collision(bound_var),
-            Pi(_, _, _) => unimplemented!(),
-            Kind(_) => false,
+fn _freshen(term: &Term<UID>, renames: &mut HashMap<usize, usize>) -> Term<UID> {
+    match term {
+        Var(x) => Var(UID {
+            name: x.name.clone(),
+            uid: *renames.get(&x.uid).unwrap_or(&x.uid),
+        }),
+        App(e1, e2) => App(box _freshen(e1, renames), box _freshen(e2, renames)),
+        Abs(x, ty, e) => {
+            let ty = _freshen(ty, renames);
+            let (x, e) = _freshen_binder(x, e, renames);
+            Abs(x, box ty, box e)
+        }
+        Pi(x, lty, rty) => {
+            let lty = _freshen(lty, renames);
+            let (x, rty) = _freshen_binder(x, rty, renames);
+            Pi(x, box lty, box rty)
         }
+        Kind(kind) => Kind(*kind),
     }
 }
+
+fn _freshen_binder(
+    x: &UID,
+    body: &Term<UID>,
+    renames: &mut HashMap<usize, usize>,
+) -> (UID, Term<UID>) {
+    let uid = fresh_uid();
+    let shadowed = renames.insert(x.uid, uid);
+    let body = _freshen(body, renames);
+    match shadowed {
+        Some(shadowed) => renames.insert(x.uid, shadowed),
+        None => renames.remove(&x.uid),
+    };
+    (
+        UID {
+            name: x.name.clone(),
+            uid,
+        },
+        body,
+    )
+}
@@ -1,7 +1,9 @@
 use crate::ast::*;
-use crate::index::bare::BareIdent;
+use crate::index::uid::conv::Conversion;
 use crate::index::uid::UID;
 
+pub(crate) const DEFAULT_LIMIT: usize = 100;
+
 macro_rules! break_by_limit {
     ($self: ident, $limit: ident) => {
         if let Some(i) = $limit {
@@ -17,14 +19,18 @@ impl Term<UID> {
         match self {
             Var(x) => {
                 if x.uid == from.uid {
-                    to.clone()
+                    to.freshen()
                 } else {
                     self.clone()
                 }
             }
-            Abs(x, ty, e) => Abs(x.clone(), ty.clone(), box e._subst(from, to)), // TODO
+            Abs(x, ty, e) => Abs(x.clone(), box ty._subst(from, to), box e._subst(from, to)),
             App(e1, e2) => App(box e1._subst(from, to), box e2._subst(from, to)),
-            Pi(_, _, _) => unimplemented!(),
+            Pi(x, lty, rty) => Pi(
+                x.clone(),
+                box lty._subst(from, to),
+                box rty._subst(from, to),
+            ),
             Kind(_) => self.clone(),
         }
     }
@@ -126,10 +132,37 @@ impl Term<UID> {
     }
 }
 
+impl Term<UID> {
+    /// Reduce one step at a time, recording every intermediate term. `limit` bounds the number
+    /// of steps as in `beta_reduce`.
+    pub fn trace(&self, strategy: ReduceStrategy, limit: Option<usize>) -> Trace<UID> {
+        let mut steps = vec![self.clone()];
+        for _ in 0..limit.unwrap_or(DEFAULT_LIMIT) {
+            let last = steps.last().unwrap();
+            let next = last.beta_reduce(strategy, Some(1));
+            if next == *last {
+                return Trace {
+                    strategy,
+                    steps,
+                    normal: true,
+                };
+            }
+            steps.push(next);
+        }
+        let last = steps.last().unwrap();
+        let normal = last.beta_reduce(strategy, Some(1)) == *last;
+        Trace {
+            strategy,
+            steps,
+            normal,
+        }
+    }
+}
+
 impl Reducible for Term<UID> {
     fn subst(&self, ex: &Self) -> Self {
-        if let Abs(x, ty, e) = self {
-            // TODO
+        if let Abs(x, _, e) = self {
+            // Every copy of `ex` gets fresh binders, so uids stay unique after duplication.
             e._subst(x, ex)
         } else {
             panic!("only abstraction can be substituted.")
@@ -141,7 +174,7 @@ impl Reducible for Term<UID> {
         let limit = if let Some(l) = limit {
             Some(l)
         } else {
-            Some(100)
+            Some(DEFAULT_LIMIT)
         };
         match strategy {
             ReduceStrategy::CBN => self.cbn_reduce(limit),
@@ -154,30 +187,8 @@ impl Reducible for Term<UID> {
         }
     }
 
+    /// Alpha, beta and eta convertible terms are considered equal.
     fn equals(&self, other: &Self) -> bool {
-        /// Alpha and eta convertible terms are considered equal.
-        fn reassign_uid(term: Term<UID>) -> Term<UID> {
-            Term::<UID>::from(Term::<BareIdent>::from(term))
-        }
-
-        reassign_uid(
-            App(
-                box self.clone(),
-                box Var(UID {
-                    name: String::from("_"),
-                    uid: self.uid_generator().next(),
-                }),
-            )
-            .nf(),
-        ) == reassign_uid(
-            App(
-                box other.clone(),
-                box Var(UID {
-                    name: String::from("_"),
-                    uid: other.uid_generator().next(),
-                }),
-            )
-            .nf(),
-        )
+        self.convertible(other, None) == Conversion::Equal
     }
 }
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::*;
+use crate::index::bare::BareIdent;
+use crate::index::uid::{Conversion, UID};
+
+#[derive(Clone, Debug)]
+pub enum TypeError {
+    UnboundVariable(UID),
+    BoxHasNoType,
+    /// The term's type should have been a kind.
+    NotASort(Term<UID>, Ty<UID>),
+    /// The term is applied to an argument, but its type isn't a product.
+    NotAFunction(Term<UID>, Ty<UID>),
+    Mismatch {
+        term: Term<UID>,
+        expected: Ty<UID>,
+        found: Ty<UID>,
+    },
+    /// Conversion of two types couldn't be decided within the step limit.
+    Undecidable(Ty<UID>, Ty<UID>, usize),
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let bare = |term: &Term<UID>| Term::<BareIdent>::from(term.clone());
+        match self {
+            TypeError::UnboundVariable(x) => write!(f, "unbound variable `{}`", x.name),
+            TypeError::BoxHasNoType => write!(f, "□ has no type"),
+            TypeError::NotASort(term, ty) => write!(
+                f,
+                "`{}` has type `{}`, which is not a kind",
+                bare(term),
+                bare(ty)
+            ),
+            TypeError::NotAFunction(term, ty) => write!(
+                f,
+                "`{}` has type `{}`, which is not a product type",
+                bare(term),
+                bare(ty)
+            ),
+            TypeError::Mismatch {
+                term,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` has type `{}`, but `{}` was expected",
+                bare(term),
+                bare(found),
+                bare(expected)
+            ),
+            TypeError::Undecidable(lhs, rhs, limit) => write!(
+                f,
+                "can't decide whether `{}` and `{}` are equal within {} steps",
+                bare(lhs),
+                bare(rhs),
+                limit
+            ),
+        }
+    }
+}
+
+struct Context<'a> {
+    axioms: &'a HashMap<BareIdent, Ty<UID>>,
+    bound: HashMap<usize, Ty<UID>>,
+}
+
+impl<'a> Context<'a> {
+    fn under<R>(&mut self, x: &UID, ty: &Ty<UID>, f: impl FnOnce(&mut Self) -> R) -> R {
+        let shadowed = self.bound.insert(x.uid, ty.clone());
+        let rtn = f(self);
+        match shadowed {
+            Some(shadowed) => self.bound.insert(x.uid, shadowed),
+            None => self.bound.remove(&x.uid),
+        };
+        rtn
+    }
+
+    fn sort_of(&mut self, term: &Term<UID>) -> Result<Kinds, TypeError> {
+        match self.infer(term)?.whnf() {
+            Kind(kind) => Ok(kind),
+            ty => Err(TypeError::NotASort(term.clone(), ty)),
+        }
+    }
+
+    fn infer(&mut self, term: &Term<UID>) -> Result<Ty<UID>, TypeError> {
+        match term {
+            Kind(Kinds::Star) => Ok(Kind(Kinds::Box)),
+            Kind(Kinds::Box) => Err(TypeError::BoxHasNoType),
+            // Axiom types come from another parse, so their binders may share uids with the term.
+            Var(x) => match self.bound.get(&x.uid) {
+                Some(ty) => Ok(ty.clone()),
+                None => self
+                    .axioms
+                    .get(&x.name)
+                    .map(Term::freshen)
+                    .ok_or_else(|| TypeError::UnboundVariable(x.clone())),
+            },
+            Pi(x, lty, rty) => {
+                self.sort_of(lty)?;
+                Ok(Kind(self.under(x, lty, |ctx| ctx.sort_of(rty))?))
+            }
+            Abs(x, ty, e) => {
+                self.sort_of(ty)?;
+                let ety = self.under(x, ty, |ctx| ctx.infer(e))?;
+                let pi = Pi(x.clone(), ty.clone(), box ety);
+                self.sort_of(&pi)?;
+                Ok(pi)
+            }
+            App(e1, e2) => match self.infer(e1)?.whnf() {
+                Pi(x, lty, rty) => {
+                    let ty = self.infer(e2)?;
+                    match ty.convertible(&lty, None) {
+                        Conversion::Equal => Ok(Abs(x, lty, rty).subst(e2)),
+                        Conversion::NotEqual => Err(TypeError::Mismatch {
+                            term: *e2.clone(),
+                            expected: *lty,
+                            found: ty,
+                        }),
+                        Conversion::Unknown(limit) => Err(TypeError::Undecidable(ty, *lty, limit)),
+                    }
+                }
+                ty => Err(TypeError::NotAFunction(*e1.clone(), ty)),
+            },
+        }
+    }
+}
+
+impl Term<UID> {
+    /// Infer the type of the term in the calculus of constructions.
+    ///
+    /// Free variables are looked up by name in `axioms`.
+    pub fn type_of(&self, axioms: &HashMap<BareIdent, Ty<UID>>) -> Result<Ty<UID>, TypeError> {
+        Context {
+            axioms,
+            bound: HashMap::default(),
+        }
+        .infer(self)
+    }
+
+    /// Check the term against a type, which has to be well-formed itself.
+    pub fn check(
+        &self,
+        ty: &Ty<UID>,
+        axioms: &HashMap<BareIdent, Ty<UID>>,
+    ) -> Result<(), TypeError> {
+        let mut ctx = Context {
+            axioms,
+            bound: HashMap::default(),
+        };
+        ctx.sort_of(ty)?;
+        let found = ctx.infer(self)?;
+        match found.convertible(ty, None) {
+            Conversion::Equal => Ok(()),
+            Conversion::NotEqual => Err(TypeError::Mismatch {
+                term: self.clone(),
+                expected: ty.clone(),
+                found,
+            }),
+            Conversion::Unknown(limit) => Err(TypeError::Undecidable(found, ty.clone(), limit)),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Running terms as programs in the style of Lazy K: a program is applied to its input, the
+//! list of the bytes of stdin followed by infinitely many 256s, and evaluates to its output,
+//! a list of bytes which ends at its first number above 255. Lists are built from pairs
+//! `λf.f head tail`, and bytes are numerals of the chosen encoding, untyped for Church.
+//!
+//! The input is built lazily: it starts as a free variable, which is replaced by the next
+//! pair of the list whenever the program gets stuck on it, and by a fixed point
+//! `λf.f 256 tail` once stdin has ended.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::*;
+use crate::encoding::Encoding;
+use crate::index::bare::BareIdent;
+use crate::index::uid::{fresh_uid, DEFAULT_LIMIT, UID};
+
+/// The number every list ends with, after the last byte.
+const EOF: usize = 256;
+
+#[derive(Clone, Debug)]
+pub enum LazyKError {
+    /// An element of the output didn't reach a normal form within that many steps.
+    Limit(usize),
+    /// An element of the output which isn't a numeral.
+    NotByte(Term<BareIdent>),
+}
+
+impl Display for LazyKError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LazyKError::Limit(limit) => write!(f, "no output within {} steps", limit),
+            LazyKError::NotByte(term) => write!(f, "`{}` is not a byte", term),
+        }
+    }
+}
+
+fn star<T: IdentType>() -> Ty<T> {
+    Kind(Kinds::Star)
+}
+
+/// `λx y.x` if `first`, else `λx y.y`, which select the head and the tail of a pair.
+fn select(first: bool) -> Term<UID> {
+    let (x, y) = ("x".to_string(), "y".to_string());
+    let body = Var(if first { x.clone() } else { y.clone() });
+    Term::from(abs(x, star(), abs(y, star(), body)))
+}
+
+fn numeral(encoding: Encoding, n: usize) -> Term<BareIdent> {
+    match encoding {
+        Encoding::Church => Encoding::Untyped.nat(n),
+        encoding => encoding.nat(n),
+    }
+}
+
+/// `(λs.s s) (λs.λf.f 256 (s s))`, the list of 256s the input ends with.
+fn eof(encoding: Encoding) -> Term<BareIdent> {
+    let (s, f) = ("s".to_string(), "f".to_string());
+    let twice = app(Var(s.clone()), Var(s.clone()));
+    let pair = abs(
+        f.clone(),
+        star(),
+        app(app(Var(f), numeral(encoding, EOF)), twice.clone()),
+    );
+    app(abs(s.clone(), star(), twice), abs(s, star(), pair))
+}
+
+fn stdin() -> UID {
+    UID {
+        name: "stdin".to_string(),
+        uid: fresh_uid(),
+    }
+}
+
+/// The output of a program, produced one byte at a time as the iterator is advanced.
+pub struct LazyK<I: Iterator<Item = u8>> {
+    /// The rest of the output, in weak head normal form once a byte has been read.
+    output: Term<UID>,
+    /// The variable standing for the part of the input that hasn't been read yet.
+    unread: UID,
+    input: I,
+    encoding: Encoding,
+    limit: usize,
+    done: bool,
+}
+
+impl<I: Iterator<Item = u8>> LazyK<I> {
+    /// Run `program` on `input`, reducing each byte of the output within `limit` steps, or
+    /// `DEFAULT_LIMIT` by default.
+    pub fn new(program: &Term<UID>, input: I, encoding: Encoding, limit: Option<usize>) -> Self {
+        let unread = stdin();
+        LazyK {
+            output: app(program.clone(), Var(unread.clone())),
+            unread,
+            input,
+            encoding,
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
+            done: false,
+        }
+    }
+
+    /// Replace the unread input with a pair of its next byte and a new unread variable, or with
+    /// the endless list of 256s once the input has ended.
+    fn read(&mut self) {
+        let unread = stdin();
+        let rest = match self.input.next() {
+            Some(byte) => {
+                let f = UID {
+                    name: "f".to_string(),
+                    uid: fresh_uid(),
+                };
+                let byte = Term::from(numeral(self.encoding, usize::from(byte)));
+                Abs(
+                    f.clone(),
+                    box star(),
+                    box app(app(Var(f), byte), Var(unread.clone())),
+                )
+            }
+            None => Term::<UID>::from(eof(self.encoding)).freshen(),
+        };
+        let output = Abs(self.unread.clone(), box star(), box self.output.clone());
+        self.output = output.subst(&rest);
+        self.unread = unread;
+    }
+
+    /// Whether the head of `term` is the unread input, so it can't reduce without reading.
+    fn stuck(&self, term: &Term<UID>) -> bool {
+        let mut head = term;
+        while let App(lhs, _) = head {
+            head = lhs;
+        }
+        *head == Var(self.unread.clone())
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, LazyKError> {
+        // Reach a weak head normal form first, since the arguments of an input stuck at the head
+        // needn't have a normal form.
+        let head = loop {
+            let head = app(self.output.clone(), select(true))
+                .beta_reduce(ReduceStrategy::CBN, Some(self.limit));
+            if self.stuck(&head) {
+                self.read();
+                continue;
+            }
+            let head = head.beta_reduce(ReduceStrategy::NOR, Some(self.limit));
+            if head.beta_reduce(ReduceStrategy::NOR, Some(1)) != head {
+                return Err(LazyKError::Limit(self.limit));
+            }
+            if !head.fv().contains(&self.unread) {
+                break head;
+            }
+            self.read();
+        };
+        let head = Term::<BareIdent>::from(head);
+        match self.encoding.read_nat(&head) {
+            Some(n) if n >= EOF => Ok(None),
+            Some(n) => {
+                self.output = app(self.output.clone(), select(false))
+                    .beta_reduce(ReduceStrategy::CBN, Some(self.limit));
+                Ok(Some(n as u8))
+            }
+            None => Err(LazyKError::NotByte(head)),
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for LazyK<I> {
+    type Item = Result<u8, LazyKError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_byte().transpose();
+        self.done = !matches!(next, Some(Ok(_)));
+        next
+    }
+}
@@ -5,18 +5,60 @@ extern crate maplit;
 #[macro_use]
 extern crate pest_derive;
 
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::process::Command;
+use std::time::Instant;
+
 use index::uid::*;
 
+use crate::arena::{Node, TermArena, TermId};
 use crate::ast::ReduceStrategy::*;
-use crate::ast::{ReduceStrategy, Reducible, Term};
+use crate::ast::{Fresh, ReduceStrategy, Reducible, Term, Trace};
+use crate::blc::{parse_bits, show_bits};
+use crate::combinators::{Basis, Bracket, Comb};
+use crate::encoding::Encoding;
+use crate::env::Env;
+use crate::export::tromp::Diagram;
+use crate::export::{Child, Path};
+use crate::format::format_module;
+use crate::index::bare::fresh::{Alphabet, Avoiding, NameScheme, Numeric, Primes, Subscripts};
 use crate::index::bare::BareIdent;
-use crate::parser::parse;
+use crate::index::dbi::AlphaTerm;
+use crate::lazyk::{LazyK, LazyKError};
+use crate::parser::{parse, parse_module, parse_with};
+use crate::pretty::Syntax;
+use crate::repl::Repl;
 
+mod arena;
 mod ast;
+mod blc;
+mod cli;
+mod codegen;
+mod combinators;
+mod encoding;
+mod env;
+mod export;
+mod format;
 mod index;
+mod lazyk;
 mod parser;
+mod prelude;
+mod pretty;
+mod repl;
+mod vm;
 
 fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("repl") => Repl::default().run(),
+        Some("demo") => demo(),
+        Some(_) => std::process::exit(cli::run(args)),
+    }
+}
+
+/// Exercise the calculus end to end, panicking if anything is off.
+fn demo() {
     // !WARN! ill-typed terms
     test_reduce(
         "(λa:*.λb:*.λc:*.a (λd:*.λe:*.e (d b)) (λd:*.c) (λd:*.d)) (λa:*.λb:*.a b)",
@@ -42,6 +84,536 @@ fn main() {
     test_parse("λA:*.λB:*.πC:*.π_:π_:A.C.π_:π_:B.C.C"); // λ A: *. λ B: *. (π C: *. (A → C) → (B → C) → C)
     test_parse("πA:*.πB:*.πC:*.π_:π_:A.B.C"); // forall A B C, (A -> B) -> C
     test_parse("πA:*.πB:*.πC:*.π_:A.π_:B.C"); // forall A B C, A -> B -> C
+    test_sugar("λx y z:*.x z (y z)", "λx:*.λy:*.λz:*.x z (y z)");
+    test_sugar("∀A B C, (A → B) → C", "πA:*.πB:*.πC:*.π_:π_:A.B.C");
+    test_sugar("Π (A B:*) (x:A), B -> A", "πA:*.πB:*.πx:A.π_:B.A");
+    test_sugar("λ(A:*) (f:A → A).f", "λA:*.λf:π_:A.A.f");
+    test_sugar("\\pi A:*.A → A → A", "πA:*.π_:A.π_:A.A");
+    test_sugar("\\pix:*.x", "λpix:*.x");
+    test_sugar("\\pi_x:*.x", "λpi_x:*.x");
+    test_idents(&[
+        "x'",
+        "f₁",
+        "α_β",
+        "αβ",
+        "x′′",
+        "_x",
+        "ℕ",
+        "definitely",
+        "with_",
+        "x1'",
+        "a1b",
+    ]);
+    assert_eq!("x1'".to_string().fresh(), "x1'1");
+    assert_eq!("a1b".to_string().fresh(), "a1b1");
+    assert_eq!("x₁9".to_string().fresh(), "x₁10");
+    // `_` isn't an identifier, but the names derived from it are.
+    assert_eq!(parse("_1").unwrap(), Term::Var("_".to_string().fresh()));
+    for reserved in &["λ", "π", "Π", "*", "□", "def", "axiom", "with", "_", "1x"] {
+        assert!(parse(reserved).map_or(true, |term| term != Term::Var(reserved.to_string())));
+    }
+    test_sugar("fλx:*.x", "f (λx:*.x)");
+    test_literal("2", Encoding::Church, "λA:*.λf:A → A.λx:A.f (f x)");
+    test_literal("2", Encoding::Scott, "λs z:*.s (λs z:*.s (λs z:*.z))");
+    test_literal("false", Encoding::Church, "λA:*.λt f:A.f");
+    test_literal("true", Encoding::Scott, "λt f:*.t");
+    test_literal(
+        "[c, n]",
+        Encoding::Church,
+        "λR:*.λc1:* → R → R.λn1:R.c1 c (c1 n n1)",
+    );
+    test_literal("[x : A]", Encoding::Scott, "λc n:*.c x (λc n:*.n)");
+    test_literal("[:R]", Encoding::Church, "λR1:*.λc:R → R1 → R1.λn:R1.n");
+    test_read(
+        "(λm n:*.λf x:*.m f (n f x)) (λf x:*.f x) (λf x:*.f (f x))",
+        Encoding::Church,
+        "3",
+    );
+    test_read("λt f:*.t", Encoding::Church, "true");
+    test_read("λA:*.λt f:A.f", Encoding::Church, "false");
+    test_read(
+        "λp:*.p a (λp:*.p b c)",
+        Encoding::Church,
+        "pair(a,pair(b,c))",
+    );
+    test_read("(λx:*.[x, 2]) 1", Encoding::Church, "[1,2]");
+    test_read("[[true], [:*]]", Encoding::Church, "[[true],[]]");
+    test_read("(λn:*.λs z:*.s n) 2", Encoding::Scott, "3");
+    test_read("[λp:*.p x 0, y]", Encoding::Scott, "[pair(x,0),y]");
+    test_read("λf x:*.x f", Encoding::Church, "λf:*.λx:*.x f");
+    let untyped = Env::untyped_prelude();
+    let lazy = [NOR, HNO, HSR, CBN];
+    let normalizing = [NOR, HNO, HAP, APP];
+    test_prelude(&untyped, "S K K x", &lazy, "x");
+    test_prelude(&untyped, "B f g x", &normalizing, "f (g x)");
+    test_prelude(&untyped, "C f x y", &normalizing, "f y x");
+    test_prelude(&untyped, "W f x", &normalizing, "f x x");
+    test_prelude(&untyped, "I (K x Ω)", &lazy, "x");
+    test_prelude(&untyped, "Y (K x)", &lazy, "x");
+    test_prelude(&untyped, "Θ (K x)", &lazy, "x");
+    test_prelude(&untyped, "Z (K x)", &[CBV, APP, NOR, HAP], "x");
+    test_prelude(&untyped, "if (and True (not False)) x y", &lazy, "x");
+    test_prelude(&untyped, "or False (iszero zero)", &normalizing, "true");
+    test_prelude(
+        &untyped,
+        "add (succ zero) (succ (succ zero))",
+        &normalizing,
+        "3",
+    );
+    test_prelude(
+        &untyped,
+        "exp (succ (succ zero)) (mul (succ (succ zero)) (succ (succ zero)))",
+        &normalizing,
+        "16",
+    );
+    test_prelude(
+        &untyped,
+        "sub (pred (mul (succ (succ zero)) (succ (succ (succ zero))))) (succ zero)",
+        &normalizing,
+        "4",
+    );
+    test_prelude(&untyped, "snd (pair x (fst (pair y z)))", &lazy, "y");
+    test_prelude(
+        &untyped,
+        "map succ (cons zero (cons (succ zero) nil))",
+        &normalizing,
+        "[1,2]",
+    );
+    test_prelude(
+        &untyped,
+        "fold add zero (cons (succ zero) (cons (succ (succ zero)) nil))",
+        &normalizing,
+        "3",
+    );
+    // Literals agree with the prelude.
+    test_prelude(&untyped, "mul 3 4", &normalizing, "12");
+    test_prelude(&untyped, "map succ [1, 2]", &normalizing, "[2,3]");
+    test_prelude(&untyped, "if true x y", &lazy, "x");
+    let typed = Env::system_f_prelude();
+    test_prelude(&typed, "mul 3 4", &normalizing, "12");
+    test_prelude(
+        &typed,
+        "S Nat (Nat → Nat) Nat (K Nat (Nat → Nat)) (K Nat Nat) (I Nat 3)",
+        &normalizing,
+        "3",
+    );
+    test_prelude(
+        &typed,
+        "B Nat Nat Nat succ (C Nat Nat Nat add 1) 2",
+        &normalizing,
+        "4",
+    );
+    test_prelude(&typed, "W Nat Nat mul 3", &normalizing, "9");
+    test_prelude(
+        &typed,
+        "if Nat (or false (iszero (sub 2 3))) 1 0",
+        &normalizing,
+        "1",
+    );
+    test_prelude(
+        &typed,
+        "and (not false) (iszero (pred 1))",
+        &normalizing,
+        "true",
+    );
+    test_prelude(&typed, "exp 2 3", &normalizing, "8");
+    test_prelude(
+        &typed,
+        "pair Nat Bool (snd Bool Nat (pair Bool Nat true 5)) false",
+        &normalizing,
+        "pair(5,false)",
+    );
+    test_prelude(
+        &typed,
+        "map Nat Nat (mul 2) (cons Nat 1 [2, 3 : Nat])",
+        &normalizing,
+        "[2,4,6]",
+    );
+    test_prelude(
+        &typed,
+        "fold Nat Nat add 0 [1, 2, 3 : Nat]",
+        &normalizing,
+        "6",
+    );
+    test_pretty("(λf:*.λx:*.f x) (λf:*.λx:*.f x)", 80);
+    test_pretty("(f λx:*.x) (πA:*.A) ((A → B) → C) λy:*.y", 80);
+    test_pretty("λx:(λy:*.y).(λz:*.z) → x (λw:*.w) → πA:*.A", 80);
+    test_pretty("(λx:*.x) λy:*.(λz:*.z) λw:*.w", 80);
+    let pred = Env::system_f_prelude().elaborate(&parse("pred").unwrap());
+    test_pretty(Term::<BareIdent>::from(pred).to_string().as_str(), 60);
+    test_pretty(
+        Term::<BareIdent>::from(untyped.elaborate(&parse("Θ Z").unwrap()))
+            .to_string()
+            .as_str(),
+        30,
+    );
+    test_syntax("λA:*.λf:A → A.λx_0:A.f (f x_0)");
+    test_syntax("λpi:*.λpix:□.πA:*.(A → A) → pix");
+    test_syntax("(λx:*.x x) λy:*.y");
+    #[cfg(feature = "serde")]
+    test_json();
+    test_repl(&[
+        ("definitely", "=> definitely"),
+        ("axiomatic x", "=> axiomatic x"),
+        ("def two := λf x:*.f (f x)", "two defined"),
+        (":strategy", "NOR"),
+        (":strategy hap", ""),
+        ("(λn:*.n) two", "--> λf:*.λx:*.f (f x)\n=> 2"),
+        (":strategy NOR", ""),
+        (":step (λx:*.x) ((λy:*.y) z)", "--> (λy:*.y) z"),
+        (":step", "--> z"),
+        (":step", "z is normal"),
+        (":limit 2", ""),
+        (":reduce (λx:*.x x) (λx:*.x x)", "--> (λx:*.x x) λx:*.x x\n--> (λx:*.x x) λx:*.x x\n=> (λx:*.x x) λx:*.x x\nstopped after 2 steps"),
+        (":limit 100", ""),
+        (":type λA:*.λx:A.x", "πA:*.A → A"),
+        (":alpha (λx:*.x) (λy:*.y)", "true"),
+        (":alpha two (λf:*.λx:*.x)", "false"),
+        (":equals (λx:*.f x) f", "true"),
+        (":show dbi", ""),
+        ("two", "=> λf:*.λx:*.1 (1 0)"),
+        (":show uid", ""),
+        (":step λA:*.A", "λ{A:1}:*.{A:1} is normal"),
+        (":blc two", "0000011100111010 (16 bits)"),
+    ]);
+    test_cli(&["eval", "(λx:*.x) y"], cli::Status::Ok);
+    #[cfg(feature = "serde")]
+    test_cli(
+        &["eval", "(λx:*.x x) λx:*.x x", "--limit", "3", "--json"],
+        cli::Status::Limit,
+    );
+    test_cli(&["eval", "λx:*."], cli::Status::ParseError);
+    test_cli(
+        &["eval", "99999999999999999999999"],
+        cli::Status::ParseError,
+    );
+    test_cli(&["eval", "10001"], cli::Status::ParseError);
+    assert!(parse("2f").is_err());
+    assert_eq!(
+        parse("2 f").unwrap(),
+        Term::App(box parse("2").unwrap(), box Term::Var("f".to_string()))
+    );
+    test_cli(&["eval", "x", "--strategy", "XYZ"], cli::Status::Usage);
+    test_cli(
+        &["check", "examples/church.lq", "--limit", "1000"],
+        cli::Status::Ok,
+    );
+    test_cli(&["check", "examples/church.lq"], cli::Status::Error);
+    test_cli(
+        &["fmt", "examples/church.lq", "--width", "40"],
+        cli::Status::Ok,
+    );
+    test_fmt(&fs::read_to_string("examples/church.lq").unwrap(), 40, None);
+    test_fmt(
+        "\n-- the identity\ndef id := \\(A:*) (x:A).x  -- polymorphic\n\n\n\
+         def k : \\pi A B:*. A -> B -> A := \\A B:*.\\x:A.\\y:B.x\n\
+         def e := [:Nat]  def u := []\n\
+         {- kept {- nested -} -}\n#eval (λx:*.x x) {- as is -} y with CBN\n\
+         #assert k 2 [1, 2 : Nat] == 2\n",
+        24,
+        Some(
+            "-- the identity\ndef id := λ(A:*) (x:A).x -- polymorphic\n\n\
+             def k : πA B:*.\n  A → B → A :=\n  λA B:*.λx:A.λy:B.x\n\
+             def e := [:Nat]\ndef u := □\n\
+             {- kept {- nested -} -}\n#eval (λx:*.x x) {- as is -} y with CBN\n\
+             #assert\n  k 2 [1, 2 : Nat]\n  == 2",
+        ),
+    );
+    test_cli(&["convert", "λx y:*.x", "--to", "dbi"], cli::Status::Ok);
+    test_cli(
+        &[
+            "convert",
+            "(λx:*.x) ((λy:*.y) z)",
+            "--to",
+            "dot",
+            "--redex",
+            "1",
+        ],
+        cli::Status::Ok,
+    );
+    test_cli(
+        &["convert", "λx:*.x", "--to", "svg", "--redex", "0"],
+        cli::Status::Usage,
+    );
+    test_export(
+        "(λx:*.λy:*.x) ((λz:*.z) w)",
+        &[vec![], vec![Child::Arg]],
+        &[
+            "n0 [label=\"@\"];",
+            "n1 [label=\"λ{x:",
+            "n6 [label=\"@\", style=filled, fillcolor=\"#ffe08a\"];",
+            "n1 -> n2 [label=\":\"];",
+            "n5 -> n1 [style=dashed, constraint=false, color=gray];",
+            "n9 -> n7 [style=dashed, constraint=false, color=gray];",
+        ],
+    );
+    test_diagram("λx:*.x", " ┬\n │\n ╵\n");
+    test_diagram("y z", " ╷ ╷\n │ │\n ├─┘\n │\n ╵\n");
+    test_diagram(
+        "λf x:*.f (f x)",
+        " ┬─┬──\n │ │\n ┼─┼─┬\n │ │ │\n │ ├─┘\n │ │\n ├─┘\n │\n ╵\n",
+    );
+    test_animation("(λx:*.x x) (λy:*.y)", 3);
+    test_cli(
+        &["eval", "(λx:*.x) λy:*.y", "--trace", "--diagram", "svg"],
+        cli::Status::Ok,
+    );
+    test_cli(&["convert", "λx:*.x x", "--to", "diagram"], cli::Status::Ok);
+    test_combinators("λx:*.x", &["I", "W K", "I", "W K", "I", "W K"]);
+    test_combinators(
+        "λf x:*.f (f x)",
+        &[
+            "S (S (K S) (S (K K) I)) (S (S (K S) (S (K K) I)) (K I))",
+            "W (B (C (K W)) (W (B (C (W (B (C (K B)) (W (B (C (K C)) (W (B (C (K K)) (W K)))))))) \
+             (W (B (C (K W)) (W (B (C (W (B (C (K B)) (W (B (C (K C)) (W (B (C (K K)) (W K)))))))) \
+             (W (B (C (K W)) (K K))))))))))",
+            "S (S (K S) K) I",
+            "W (B (C (K W)) (W (B (C (W (B (C (K B)) (W (B (C (K C)) K))))) (W K))))",
+            "S (S (K S) K) I",
+            "W (B (C B) (W K))",
+        ],
+    );
+    test_combinators(
+        "λx y:*.y x",
+        &[
+            "S (S (K S) (K I)) (S (K K) I)",
+            "W (B (C (K W)) (W (B (C (W (B (C (K B)) (W (B (C (K C)) (W (B (C (K W)) (K K)))))))) \
+             (W (B (C (K K)) (W K))))))",
+            "S (K (S I)) K",
+            "W (B (C (K W)) (W (B (C (K (B (C (W K))))) K)))",
+            "S (K (S I)) K",
+            "C (W K)",
+        ],
+    );
+    test_combinators(
+        "λx:*.x x",
+        &[
+            "S I I",
+            "W (B (C (W K)) (W K))",
+            "S I I",
+            "W (B (C (W K)) (W K))",
+            "S I I",
+            "W (B (C (W K)) (W K))",
+        ],
+    );
+    test_comb_reduce("(λx y z:*.x z (y z)) (λx y:*.x) (λx y:*.x) a", "a");
+    test_comb_reduce("(λf x:*.f (f x)) (λf x:*.f (f x)) g a", "g (g (g (g a)))");
+    test_blc("λx:*.x", "0010");
+    test_blc("λf x:*.f (f x)", "0000011100111010");
+    test_blc("λx y:*.x y (λz:*.z x)", "00000101110100001101110");
+    test_blc_run("λl:*.l", b"hi", b"hi");
+    test_blc_run("λl:*.l (λh t:*.λz:*.z h (λx y:*.y))", b"hello", b"h");
+    assert!(Term::<UID>::from_blc(&parse_bits("0010 0").unwrap()).is_err());
+    assert!(Term::<UID>::from_blc(&parse_bits("0011110").unwrap()).is_err());
+    test_lazy_k("λl:*.l", b"hello", Encoding::Church, None, b"hello");
+    test_cli(&["run", "--encoding", "church"], cli::Status::Usage);
+    for bits in ["01", "0012", "10", "0010 1"] {
+        test_cli(&["blc", bits], cli::Status::ParseError);
+    }
+    // The head of the output is the whole input, which has no normal form.
+    let program = Term::<UID>::from(parse("λl:*.λf:*.f l l").unwrap());
+    let mut output = LazyK::new(&program, b"ab".iter().cloned(), Encoding::Church, None);
+    assert!(matches!(output.next(), Some(Err(LazyKError::Limit(_)))));
+    assert!(output.next().is_none());
+    // Reading past the end of the input gives more 256s.
+    let drop2 = "λl:*.l (λh t:*.t) (λh t:*.t)";
+    test_lazy_k(drop2, b"abc", Encoding::Church, None, b"c");
+    test_lazy_k(drop2, b"a", Encoding::Church, None, b"");
+    test_lazy_k(drop2, b"", Encoding::Scott, None, b"");
+    test_lazy_k(
+        "λl:*.l (λh t:*.t)",
+        b"hello",
+        Encoding::Church,
+        None,
+        b"ello",
+    );
+    test_lazy_k(
+        "λl:*.l (λa r:*.r (λb t:*.λf:*.f b (λg:*.g a t)))",
+        b"hello",
+        Encoding::Scott,
+        None,
+        b"ehllo",
+    );
+    test_lazy_k(
+        "λl:*.l (λh t:*.(λf:*.(λx:*.f (x x)) (λx:*.f (x x))) (λs f:*.f h s))",
+        b"yes",
+        Encoding::Church,
+        Some(4),
+        b"yyyy",
+    );
+    test_vm("λx:*.(λy:*.y) x", "λx:*.x");
+    test_vm("λA:*.λx:A.(λB:*.λy:B.y) A x", "λA:*.λx:A.x");
+    test_vm("exp 2 3", "8");
+    test_vm("map Nat Nat (mul 2) [1, 2, 3 : Nat]", "[2,4,6]");
+    test_vm_speed("exp 3 4", 2);
+    test_vm_speed("exp 2 10", 10);
+    test_cli(
+        &["eval", "(λx y:*.x) a ((λx:*.x x) λx:*.x x)", "--vm", "lazy"],
+        cli::Status::Ok,
+    );
+    test_cli(
+        &[
+            "eval",
+            "(λx y:*.x) a ((λx:*.x x) λx:*.x x)",
+            "--vm",
+            "strict",
+        ],
+        cli::Status::Limit,
+    );
+    test_cli(&["eval", "x", "--vm", "eager"], cli::Status::Usage);
+    test_c(&Env::system_f_prelude(), "exp 2 3");
+    test_c(
+        &Env::system_f_prelude(),
+        "fold Nat Nat add 0 [1, 2, 3 : Nat]",
+    );
+    test_c(&Env::system_f_prelude(), "if Nat (iszero (sub 3 5)) 7 9");
+    test_c(&Env::system_f_prelude(), "(λx y:Nat.x) 5 ((λx:Nat.x) 3)");
+    test_c(
+        &Env::untyped_prelude(),
+        "(λtwo:*.pred (exp two (succ two))) (succ (succ zero))",
+    );
+    // `if` evaluates both branches by value, so they are delayed behind `λu`.
+    test_c(
+        &Env::untyped_prelude(),
+        "Z (λfact n:*.iszero n (λu:*.succ zero) (λu:*.mul n (fact (pred n))) I) \
+         (succ (succ (succ (succ zero))))",
+    );
+    assert_eq!(
+        Term::<UID>::from(parse("λx:*.y x").unwrap())
+            .to_c()
+            .map_err(|e| e.to_string()),
+        Err("`y` is free, only closed terms can be compiled".to_string())
+    );
+    test_cli(
+        &["convert", "exp 2 3", "--to", "c", "--prelude", "system-f"],
+        cli::Status::Ok,
+    );
+    test_emit("(λx:*.x) y", "(x => x)(y)", "((lambda (x) x) y)");
+    test_emit("f (λx:*.x) z", "f(x => x)(z)", "((f (lambda (x) x)) z)");
+    test_emit(
+        "λf:*.f (λx:*.x) ∀A:*, A",
+        "f => f(x => x)(null)",
+        "(lambda (f) ((f (lambda (x) x)) '()))",
+    );
+    test_emit(
+        "λx₁ x1 if x':*.x₁ x1 if x'",
+        "x1 => x1_1 => if$ => x$ => x1(x1_1)(if$)(x$)",
+        "(lambda (x1) (lambda (x1_1) (lambda (if*) (lambda (x*) (((x1 x1_1) if*) x*)))))",
+    );
+    test_emit("λx:*.x x1 x", "x => x(x1)(x)", "(lambda (x) ((x x1) x))");
+    test_emit(
+        "λx1:*.x₁ x1",
+        "x1_1 => x1(x1_1)",
+        "(lambda (x1_1) (x1 x1_1))",
+    );
+    assert_eq!(
+        parse("λf x:*.f (f x)").unwrap().to_js(true, 80),
+        "f => x => f()(() => f()(x))"
+    );
+    assert_eq!(
+        parse("λf x:*.f (f x)").unwrap().to_scheme(true, 80),
+        "(lambda (f) (lambda (x) ((f) (lambda () ((f) x)))))"
+    );
+    test_js(&Env::system_f_prelude(), "exp 2 3", true, true);
+    test_js(
+        &Env::system_f_prelude(),
+        "fold Nat Nat add 0 [1, 2, 3 : Nat]",
+        true,
+        true,
+    );
+    test_js(
+        &Env::untyped_prelude(),
+        "(λtwo:*.pred (exp two (succ two))) (succ (succ zero))",
+        false,
+        true,
+    );
+    // Only call-by-name ignores the diverging branch.
+    test_js(
+        &Env::untyped_prelude(),
+        "Y (λfact n:*.iszero n (succ zero) (mul n (fact (pred n)))) \
+         (succ (succ (succ zero)))",
+        false,
+        false,
+    );
+    test_js(&Env::untyped_prelude(), "add 2 3", false, true);
+    test_scheme(&Env::system_f_prelude(), "exp 2 3", true, true);
+    test_scheme(
+        &Env::untyped_prelude(),
+        "(λtwo:*.pred (exp two (succ two))) (succ (succ zero))",
+        false,
+        true,
+    );
+    test_scheme(
+        &Env::untyped_prelude(),
+        "Y (λfact n:*.iszero n (succ zero) (mul n (fact (pred n)))) \
+         (succ (succ (succ zero)))",
+        false,
+        false,
+    );
+    test_cli(
+        &["convert", "exp 2 3", "--to", "js", "--prelude", "system-f"],
+        cli::Status::Ok,
+    );
+    test_cli(
+        &["convert", "λx':*.x'", "--to", "scheme", "--thunks"],
+        cli::Status::Ok,
+    );
+    test_layout(
+        "-- twice, applied to the identity\n\
+         (λf:*. λx:*.\n\
+         \t f  (f x))\n\
+         {- the {- nested -} identity -} (λy : * . y)  ",
+        "(λf:*.λx:*.f (f x)) (λy:*.y)",
+    );
+    test_module(include_str!("../examples/church.lq"), &[]);
+    // Binders of axiom types don't capture the variables of the term they are applied in.
+    test_module(
+        "axiom k : πA:*.πB:*.A → B → A\naxiom X : *\naxiom Y : *\n\
+         axiom x : X\naxiom y : Y\n#check k X Y\n#check k X Y x y",
+        &["k X Y : X → Y → X", "k X Y x y : X"],
+    );
+    assert!(parser::parse_module("defx := y").is_err());
+    assert!(parser::parse_module("axiomx : *").is_err());
+    assert_eq!(parser::parse_module("def x := y").unwrap().len(), 1);
+    test_names(
+        "λA:*.λB:*.πC:*.π_:π_:A.C.π_:π_:B.C.C",
+        "λA:*.λB:*.πC:*.(A → C) → (B → C) → C",
+    );
+    test_names("λx:*.x (λx:*.x) y", "λx:*.x (λx:*.x) y");
+    test_names("λT:*.λx:T.λT:*.λy:T.x", "λT:*.λx:T.λT:*.λy:T.x");
+    test_names("λx1:*.λx:*.x1 x", "λx1:*.λx:*.x1 x");
+    test_names("(λy:*.λx:*.y x) x", "λx1:*.x x1");
+    test_names(
+        "λx1:*.(λy:*.λx:*.λw:*.y x x1 w) x",
+        "λx1:*.λx2:*.λw:*.x x2 x1 w",
+    );
+    test_schemes(
+        "λx:*.λx1:*.(λa:*.λb:*.λx:*.z a b x) x x1",
+        &[
+            "λx:*.λx1:*.λx2:*.z x x1 x2",
+            "λx:*.λx1:*.λx':*.z x x1 x'",
+            "λx:*.λx1:*.λx₁:*.z x x1 x₁",
+            "λx:*.λx1:*.λy:*.z x x1 y",
+            "λx:*.λx1:*.λx₂:*.z x x1 x₂",
+        ],
+    );
+    test_arena("(λf:*.λx:*.f x) (λf:*.λx:*.f x)");
+    test_arena("(λx:*.x x) (λx:*.x x)");
+    // The second copy of `λz` captures the first unless the inner redex renames it.
+    test_arena("(λx:*.x x) (λy:*.λz:*.y z)");
+    test_arena("λa:*.(λf:*.λx:*.f (f x)) (λf:*.λx:*.f (f x)) a");
+    test_alpha("λx:*.λy:*.x y", "λa:*.λb:*.a b", true);
+    test_alpha("λx:*.λy:*.x y", "λy:*.λx:*.x y", false);
+    test_alpha("πA:*.π_:A.A", "πB:*.π_:B.B", true);
+    test_alpha("λx:*.y", "λx:*.z", false);
+    test_conv("λx:*.f x", "f", Conversion::Equal);
+    test_conv("λx:*.λy:*.x", "λx:*.λy:*.y", Conversion::NotEqual);
+    test_conv(
+        "(λm:*.λn:*.λf:*.λx:*.m f (n f x)) (λf:*.λx:*.f x) (λf:*.λx:*.f x)",
+        "λf:*.λx:*.f (f x)",
+        Conversion::Equal,
+    );
+    test_conv("(λx:*.x x) (λx:*.x x)", "y", Conversion::Unknown(100));
 }
 
 fn test_parse(expr: &str) {
@@ -53,20 +625,572 @@ fn test_parse(expr: &str) {
     )
 }
 
+fn test_sugar(sugar: &str, core: &str) {
+    let term = parse(sugar).unwrap();
+    println!("\n{}\n{}", sugar, term);
+    assert_eq!(term, parse(core).unwrap());
+    assert!(parse(term.to_string().as_str()).unwrap().alpha_eq(&term));
+}
+
+/// Formatting keeps the declarations, only changes the layout of those without comments and
+/// is idempotent.
+fn test_fmt(source: &str, width: usize, expected: Option<&str>) {
+    let formatted = format_module(source, width).unwrap();
+    println!("\n{}", formatted);
+    if let Some(expected) = expected {
+        assert_eq!(formatted, expected);
+    }
+    assert_eq!(format_module(&formatted, width).unwrap(), formatted);
+    assert_eq!(
+        parse_module(&formatted).unwrap(),
+        parse_module(source).unwrap()
+    );
+    let comments = |line: &str| line.contains("--") || line.contains("{-");
+    for line in formatted.lines().filter(|line| !comments(line)) {
+        assert!(line.chars().count() <= width, "`{}` is too long", line);
+    }
+    let count = |text: &str, pattern| text.matches(pattern).count();
+    for pattern in ["--", "{-", "\n\n"] {
+        assert_eq!(count(&formatted, pattern), count(source.trim(), pattern));
+    }
+}
+
+fn test_idents(names: &[&str]) {
+    for name in names {
+        let mut name = name.to_string();
+        for _ in 0..3 {
+            print!("{} ", name);
+            assert_eq!(parse(name.as_str()).unwrap(), Term::Var(name.clone()));
+            name = name.fresh();
+        }
+    }
+    println!();
+}
+
+fn test_literal(source: &str, encoding: Encoding, expected: &str) {
+    let term = parse_with(source, encoding).unwrap();
+    println!("\n{} ({:?})\n{}", source, encoding, term);
+    assert_eq!(term, parse(expected).unwrap());
+}
+
+fn test_read(source: &str, encoding: Encoding, expected: &str) {
+    let term = Term::<UID>::from(parse_with(source, encoding).unwrap()).nf();
+    let value = encoding.read(&Term::<BareIdent>::from(term));
+    println!("\n{} ({:?})\n{}", source, encoding, value);
+    assert_eq!(value.to_string(), expected);
+}
+
+fn test_prelude(env: &Env, expr: &str, strategies: &[ReduceStrategy], expected: &str) {
+    let term = env.elaborate(&env.parse(expr).unwrap());
+    match term.type_of(&HashMap::default()) {
+        Ok(ty) => println!("\n{} : {}", expr, Term::<BareIdent>::from(ty)),
+        Err(_) => println!("\n{}", expr),
+    }
+    for strategy in strategies {
+        let result = Term::<BareIdent>::from(term.beta_reduce(*strategy, Some(1000)));
+        let value = Encoding::Church.read(&result);
+        println!("{:?}: {}", strategy, value);
+        assert_eq!(value.to_string(), expected);
+    }
+}
+
+fn test_pretty(expr: &str, width: usize) {
+    let term = parse(expr).unwrap();
+    let pretty = format!("{:width$}", term, width = width);
+    println!("\n{}", pretty);
+    assert_eq!(parse(pretty.as_str()).unwrap(), term);
+    assert!(pretty.lines().all(|line| line.chars().count() <= width));
+}
+
+fn test_syntax(expr: &str) {
+    let term = parse(expr).unwrap();
+    for syntax in &[Syntax::Ascii, Syntax::Unicode, Syntax::Latex, Syntax::SExpr] {
+        println!("{:?}: {}", syntax, term.display(*syntax));
+    }
+    for syntax in &[Syntax::Ascii, Syntax::Unicode] {
+        assert_eq!(
+            parse(term.display(*syntax).to_string().as_str()).unwrap(),
+            term
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+fn test_json() {
+    let term = parse("λx:*.f x").unwrap();
+    let json = serde_json::to_string(&term).unwrap();
+    println!("\n{}", json);
+    assert_eq!(
+        json,
+        r#"{"abs":["x",{"kind":"star"},{"app":[{"var":"f"},{"var":"x"}]}]}"#
+    );
+    assert_eq!(
+        serde_json::from_str::<Term<BareIdent>>(&json).unwrap(),
+        term
+    );
+
+    let expr = Term::<UID>::from(parse("(λf:*.λx:*.f x) (λy:*.y)").unwrap());
+    let trace = expr.trace(NOR, None);
+    let json = serde_json::to_string(&trace).unwrap();
+    println!("{}", json);
+    let trace_: Trace<UID> = serde_json::from_str(&json).unwrap();
+    assert_eq!(trace_, trace);
+    assert!(trace_.normal);
+    let far: UID = serde_json::from_str(r#"{"name":"z","uid":1000000000}"#).unwrap();
+    assert!(fresh_uid() > far.uid);
+}
+
+fn test_repl(session: &[(&str, &str)]) {
+    let mut repl = Repl::default();
+    for (line, expected) in session {
+        let output = repl.eval(line).unwrap();
+        println!("λ> {}\n{}", line, output);
+        assert_eq!(output, *expected);
+    }
+    assert!(repl.eval(":nope").is_err());
+    assert!(repl.eval(":strategy XYZ").is_err());
+    assert!(repl.eval(":load /nonexistent.lq").is_err());
+}
+
+fn test_cli(args: &[&str], expected: cli::Status) {
+    println!("\nlq {}", args.join(" "));
+    let args = args.iter().map(ToString::to_string).collect();
+    assert_eq!(cli::run(args), expected as i32);
+}
+
+fn test_export(expr: &str, redexes: &[Path], edges: &[&str]) {
+    let expr = Term::<UID>::from(parse(expr).unwrap());
+    assert_eq!(expr.redexes(), redexes);
+    for redex in redexes {
+        assert!(matches!(expr.at(redex), Some(Term::App(_, _))));
+    }
+    let dot = expr.to_dot(redexes.last().map(Vec::as_slice));
+    println!("\n{}", dot);
+    for edge in edges {
+        assert!(dot.contains(edge), "missing `{}`", edge);
+    }
+    let svg = expr.to_svg(redexes.last().map(Vec::as_slice));
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    let nodes = dot
+        .lines()
+        .filter(|line| line.contains(" [label=") && !line.contains("->"));
+    assert_eq!(svg.matches("<ellipse").count(), nodes.count());
+    assert_eq!(svg.matches("<path").count(), dot.matches("dashed").count());
+}
+
+fn test_diagram(expr: &str, expected: &str) {
+    let diagram = Diagram::new(&parse(expr).unwrap());
+    println!("\n{}\n{}", expr, diagram.to_text());
+    assert_eq!(diagram.to_text(), expected);
+    assert!(diagram.to_svg().ends_with("</svg>\n"));
+}
+
+fn test_animation(expr: &str, frames: usize) {
+    let expr = Term::<UID>::from(parse(expr).unwrap());
+    let trace = Trace::<BareIdent>::from(expr.trace(NOR, None));
+    for diagram in trace.diagrams() {
+        println!("{}", diagram.to_text());
+    }
+    let svg = trace.to_animated_svg();
+    assert_eq!(trace.diagrams().len(), frames);
+    assert_eq!(svg.matches("<animate ").count(), frames);
+}
+
+/// Translations with each bracket abstraction, from naive to Turner's, in SKI then BCKW.
+fn test_combinators(expr: &str, expected: &[&str]) {
+    let term = parse(expr).unwrap();
+    let original = Term::<UID>::from(term.clone());
+    println!("\n{}", expr);
+    let mut translations = vec![];
+    for bracket in [Bracket::Naive, Bracket::Eta, Bracket::Turner] {
+        for basis in [Basis::Ski, Basis::Bckw] {
+            let comb = Comb::from_term(&term, basis, bracket).unwrap();
+            println!("{:?} {:?} ({}): {}", bracket, basis, comb.size(), comb);
+            let lambda = Term::<UID>::from(comb.to_term());
+            // Naive translations take more steps to convert back than `equals` allows.
+            assert_eq!(
+                lambda.convertible(&original, Some(10_000)),
+                Conversion::Equal
+            );
+            if bracket == Bracket::Turner {
+                assert!(lambda.equals(&original));
+            }
+            if basis == Basis::Ski {
+                assert!(!comb.to_string().contains(['B', 'C', 'W']));
+            }
+            translations.push(comb.to_string());
+        }
+    }
+    assert_eq!(translations, expected);
+}
+
+fn test_comb_reduce(expr: &str, expected: &str) {
+    let comb = Comb::from_term(&parse(expr).unwrap(), Basis::Ski, Bracket::Turner).unwrap();
+    let (result, normal) = comb.reduce(None);
+    println!("\n{} => {}", comb, result);
+    assert!(normal);
+    assert_eq!(result.to_string(), expected);
+    assert!(comb.step().and_then(|comb| comb.step()).is_some());
+}
+
+fn test_blc(expr: &str, expected: &str) {
+    let term = Term::<UID>::from(parse(expr).unwrap());
+    let bits = term.to_blc().unwrap();
+    println!(
+        "\n{} = {} ({} bits)",
+        expr,
+        show_bits(&bits),
+        term.size_in_bits().unwrap()
+    );
+    assert_eq!(show_bits(&bits), expected);
+    let decoded = Term::<BareIdent>::from(Term::<UID>::from_blc(&bits).unwrap());
+    assert!(decoded.alpha_eq(&Term::from(term)));
+}
+
+fn test_blc_run(program: &str, input: &[u8], expected: &[u8]) {
+    let program = Term::<UID>::from(parse(program).unwrap());
+    let output = blc::run(&program, input, None).unwrap();
+    println!("\n{} {:?} => {:?}", program, input, output);
+    assert_eq!(output, expected);
+}
+
+/// Run a Lazy K program, keeping the first `take` bytes of its output if it's infinite.
+fn test_lazy_k(
+    program: &str,
+    input: &'static [u8],
+    encoding: Encoding,
+    take: Option<usize>,
+    expected: &[u8],
+) {
+    let program = Term::<UID>::from(parse(program).unwrap());
+    let output = LazyK::new(&program, input.iter().cloned(), encoding, None)
+        .take(take.unwrap_or(usize::MAX))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    println!(
+        "\n{} {:?} => {:?}",
+        Term::<BareIdent>::from(program),
+        String::from_utf8_lossy(input),
+        String::from_utf8_lossy(&output)
+    );
+    assert_eq!(output, expected);
+}
+
+/// Both modes of the virtual machine agree with normal order reduction.
+fn test_vm(expr: &str, expected: &str) {
+    let env = Env::system_f_prelude();
+    let term = env.elaborate(&env.parse(expr).unwrap());
+    let nf = Term::<BareIdent>::from(term.beta_reduce(NOR, Some(10_000)));
+    for mode in [vm::Mode::Strict, vm::Mode::Lazy] {
+        let result = Term::<BareIdent>::from(term.vm_nf(mode, Some(10_000)).unwrap());
+        let value = Encoding::default().read(&result);
+        println!("\n{} ~{:?}~> {}", expr, mode, value);
+        assert_eq!(value.to_string(), expected);
+        assert!(result.alpha_eq(&nf));
+    }
+}
+
+/// The virtual machine agrees with `CBV` and is at least `speedup` times faster, which leaves
+/// a wide margin on the actual ratio.
+fn test_vm_speed(expr: &str, speedup: u32) {
+    let env = Env::system_f_prelude();
+    let term = env.elaborate(&env.parse(expr).unwrap());
+    let start = Instant::now();
+    let vm = term.vm_nf(vm::Mode::Strict, Some(1_000_000)).unwrap();
+    let vm_time = start.elapsed();
+    // `CBV` stops at weak normal forms, which still have to be normalized.
+    let start = Instant::now();
+    let cbv = term
+        .beta_reduce(CBV, Some(1_000_000))
+        .beta_reduce(NOR, Some(1_000_000));
+    let cbv_time = start.elapsed();
+    println!("\n{}: CBV {:?}, VM {:?}", expr, cbv_time, vm_time);
+    assert!(Term::<BareIdent>::from(vm).alpha_eq(&Term::from(cbv)));
+    assert!(cbv_time > speedup * vm_time);
+}
+
+/// Compile the term with the system C compiler, if there is one, and compare the numeral the
+/// program prints with the normal form.
+fn test_c(env: &Env, expr: &str) {
+    let term = env.elaborate(&env.parse(expr).unwrap());
+    let nf = Term::<BareIdent>::from(term.beta_reduce(NOR, Some(100_000)));
+    let expected = Encoding::default().read(&nf).to_string();
+    let dir = std::env::temp_dir().join(format!("lq-c-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (source, binary) = (dir.join("main.c"), dir.join("main"));
+    fs::write(&source, term.to_c().unwrap()).unwrap();
+    let compiled = Command::new("cc")
+        .args(["-std=c99", "-O2", "-Wall", "-Werror", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .status();
+    match compiled {
+        Ok(status) => assert!(status.success()),
+        Err(_) => {
+            println!("\n{}: no C compiler, skipped", expr);
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+    }
+    let output = Command::new(&binary).output().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    let printed = String::from_utf8(output.stdout).unwrap();
+    println!("\n{} ~C~> {}", expr, printed.trim_end());
+    assert_eq!(printed.trim_end(), expected);
+}
+
+fn test_emit(expr: &str, js: &str, scheme: &str) {
+    let term = parse(expr).unwrap();
+    println!(
+        "\n{}\n{}\n{}",
+        expr,
+        term.to_js(false, 80),
+        term.to_scheme(false, 80)
+    );
+    assert_eq!(term.to_js(false, 80), js);
+    assert_eq!(term.to_scheme(false, 80), scheme);
+}
+
+/// Run the JavaScript code of the term with node, if it is installed, decoding the numeral it
+/// evaluates to with the successor of numbers. Terms which only have a normal form by name are
+/// not run `by_value`.
+fn test_js(env: &Env, expr: &str, typed: bool, by_value: bool) {
+    let term = env.elaborate(&env.parse(expr).unwrap());
+    let nf = Term::<BareIdent>::from(term.beta_reduce(NOR, Some(100_000)));
+    let expected = Encoding::default().read(&nf).to_string();
+    let term = Term::<BareIdent>::from(term);
+    let modes = if by_value {
+        vec![false, true]
+    } else {
+        vec![true]
+    };
+    for thunks in modes {
+        let decode = match (typed, thunks) {
+            (false, false) => "(n => n + 1)(0)",
+            (true, false) => "(null)(n => n + 1)(0)",
+            (false, true) => "(() => n => n() + 1)(() => 0)",
+            (true, true) => "(() => null)(() => n => n() + 1)(() => 0)",
+        };
+        let program = format!("console.log(({}){})", term.to_js(thunks, 80), decode);
+        let output = match Command::new("node").args(["-e", &program]).output() {
+            Ok(output) => output,
+            Err(_) => {
+                println!("\n{}: no node, skipped", expr);
+                return;
+            }
+        };
+        let printed = String::from_utf8(output.stdout).unwrap();
+        println!(
+            "\n{} ~JS~> {} (thunks: {})",
+            expr,
+            printed.trim_end(),
+            thunks
+        );
+        assert_eq!(printed.trim_end(), expected);
+    }
+}
+
+/// Run the Scheme code of the term with the first interpreter installed among Guile, Chez
+/// Scheme and Racket, if any, like `test_js`.
+fn test_scheme(env: &Env, expr: &str, typed: bool, by_value: bool) {
+    let term = env.elaborate(&env.parse(expr).unwrap());
+    let nf = Term::<BareIdent>::from(term.beta_reduce(NOR, Some(100_000)));
+    let expected = Encoding::default().read(&nf).to_string();
+    let term = Term::<BareIdent>::from(term);
+    let interpreters: [(&str, &str); 4] = [
+        ("guile", "-s"),
+        ("chez", "--script"),
+        ("scheme", "--script"),
+        ("racket", "-f"),
+    ];
+    let dir = std::env::temp_dir().join(format!("lq-scheme-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("main.scm");
+    let modes = if by_value {
+        vec![false, true]
+    } else {
+        vec![true]
+    };
+    for thunks in modes {
+        let args = match thunks {
+            false => ["'()", "(lambda (k) (+ k 1))", "0"],
+            true => [
+                "(lambda () '())",
+                "(lambda () (lambda (k) (+ (k) 1)))",
+                "(lambda () 0)",
+            ],
+        };
+        let skip = if typed { 0 } else { 1 };
+        let decoded = args[skip..]
+            .iter()
+            .fold(term.to_scheme(thunks, 80), |f, arg| {
+                format!("({} {})", f, arg)
+            });
+        fs::write(&source, format!("(display {})\n(newline)\n", decoded)).unwrap();
+        let output = interpreters.iter().find_map(|(interpreter, flag)| {
+            Command::new(interpreter)
+                .arg(flag)
+                .arg(&source)
+                .output()
+                .ok()
+        });
+        let output = match output {
+            Some(output) => output,
+            None => {
+                println!("\n{}: no Scheme interpreter, skipped", expr);
+                break;
+            }
+        };
+        let printed = String::from_utf8(output.stdout).unwrap();
+        println!(
+            "\n{} ~Scheme~> {} (thunks: {})",
+            expr,
+            printed.trim_end(),
+            thunks
+        );
+        assert_eq!(printed.trim_end(), expected);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn test_layout(source: &str, expected: &str) {
+    println!("\n{}", source);
+    println!("{}", parse(source).unwrap());
+    assert_eq!(parse(source).unwrap(), parse(expected).unwrap())
+}
+
 fn test_reduce(expr: &str, strategy: ReduceStrategy) {
     let expr = parse(expr).unwrap();
-    let mut expr: Term<UID> = Term::from(expr);
+    let expr: Term<UID> = Term::from(expr);
     println!(
         "\n{} => {}",
         Term::<BareIdent>::from(expr.clone()),
         Term::<BareIdent>::from(expr.beta_reduce(strategy, None))
     );
-    loop {
-        let new_expr = expr.beta_reduce(strategy, Some(1));
-        if expr == new_expr {
-            break;
+    let trace = Trace::<BareIdent>::from(expr.trace(strategy, None));
+    for step in trace.steps.iter().skip(1) {
+        println!("--> {}", step);
+    }
+}
+
+/// Load a module, which has to give each of the `expected` responses among others.
+fn test_module(source: &str, expected: &[&str]) {
+    println!();
+    let mut env = Env::default();
+    env.limit = Some(1000);
+    let responses: Vec<_> = env
+        .load(source)
+        .unwrap_or_else(|e| panic!("{}", e))
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    for response in &responses {
+        println!("{}", response);
+    }
+    for response in expected {
+        assert!(responses.iter().any(|r| r == response), "no `{}`", response);
+    }
+}
+
+/// Binders keep their names unless they would capture a variable, after normalization.
+fn test_names(expr: &str, expected: &str) {
+    let expr = parse(expr).unwrap();
+    let named = Term::<BareIdent>::from(Term::<UID>::from(expr.clone()).nf());
+    println!("\n{} ~> {}", expr, named);
+    assert_eq!(named.to_string(), expected);
+    assert!(named.alpha_eq(&Term::from(Term::<UID>::from(expr).nf())));
+}
+
+fn test_schemes(expr: &str, expected: &[&str]) {
+    let expr = Term::<UID>::from(parse(expr).unwrap()).nf();
+    let alphabet = Alphabet::new(vec!["x".to_string(), "y".to_string(), "z".to_string()]);
+    let reserved = Avoiding::new(Subscripts, hashset! {"x₁".to_string()});
+    let schemes: Vec<(&str, &dyn NameScheme)> = vec![
+        ("numeric", &Numeric),
+        ("primes", &Primes),
+        ("subscripts", &Subscripts),
+        ("alphabet", &alphabet),
+        ("subscripts without x₁", &reserved),
+    ];
+    println!();
+    let mut named = vec![];
+    for (name, scheme) in schemes {
+        named.push(expr.to_bare_with(scheme).to_string());
+        println!("{}: {}", name, named.last().unwrap());
+    }
+    assert_eq!(named, expected);
+}
+
+/// Contract the leftmost outermost redex of `id`, rebuilding the nodes above it.
+fn arena_step(arena: &mut TermArena<UID>, id: TermId) -> Option<TermId> {
+    if let Some(reduced) = arena.contract(id) {
+        return Some(reduced);
+    }
+    match arena[id].clone() {
+        Node::App(e1, e2) => match arena_step(arena, e1) {
+            Some(e1) => Some(arena.app(e1, e2)),
+            None => arena_step(arena, e2).map(|e2| arena.app(e1, e2)),
+        },
+        Node::Abs(x, ty, e) => arena_step(arena, e).map(|e| arena.abs(x, ty, e)),
+        _ => None,
+    }
+}
+
+fn test_arena(expr: &str) {
+    let expr: Term<UID> = Term::from(parse(expr).unwrap());
+    let mut arena = TermArena::default();
+    let id = arena.insert(&expr);
+    assert_eq!(arena.extract(id), expr);
+    assert_eq!(arena.insert(&expr), id);
+    if let Some(reduced) = arena.contract(id) {
+        println!(
+            "\n{} --> {} ({} nodes)",
+            Term::<BareIdent>::from(expr.clone()),
+            Term::<BareIdent>::from(arena.extract(reduced)),
+            arena.node_count()
+        );
+        assert!(arena
+            .extract(reduced)
+            .alpha_eq(&expr.beta_reduce(CBN, Some(1))));
+    }
+    // Below the root too, where substitutions may have duplicated binders.
+    let mut normal = id;
+    for _ in 0..10 {
+        match arena_step(&mut arena, normal) {
+            Some(next) => normal = next,
+            None => break,
         }
-        println!("--> {}", Term::<BareIdent>::from(new_expr.clone()));
-        expr = new_expr;
     }
+    let nf = expr.beta_reduce(NOR, Some(10));
+    println!(
+        "\n{} -->> {}",
+        Term::<BareIdent>::from(expr.clone()),
+        Term::<BareIdent>::from(arena.extract(normal))
+    );
+    assert!(Term::<BareIdent>::from(arena.extract(normal)).alpha_eq(&Term::from(nf)));
+}
+
+fn test_alpha(lhs: &str, rhs: &str, expected: bool) {
+    let (lhs, rhs) = (parse(lhs).unwrap(), parse(rhs).unwrap());
+    println!("\n{} =α {} : {}", lhs, rhs, lhs.alpha_eq(&rhs));
+    assert_eq!(lhs.alpha_eq(&rhs), expected);
+    assert_eq!(lhs.alpha_hash() == rhs.alpha_hash(), expected);
+    let terms: HashSet<_> = vec![AlphaTerm::new(lhs), AlphaTerm::new(rhs)]
+        .into_iter()
+        .collect();
+    assert_eq!(terms.len(), if expected { 1 } else { 2 });
+    terms.iter().for_each(|term| println!("  {}", term.term()));
+}
+
+fn test_conv(lhs: &str, rhs: &str, expected: Conversion) {
+    let lhs = Term::<UID>::from(parse(lhs).unwrap());
+    let rhs = Term::<UID>::from(parse(rhs).unwrap());
+    let result = lhs.convertible(&rhs, None);
+    println!(
+        "\n{} =β {} : {:?}",
+        Term::<BareIdent>::from(lhs),
+        Term::<BareIdent>::from(rhs),
+        result
+    );
+    assert_eq!(result, expected);
 }
@@ -1,16 +1,29 @@
 use std::result;
 
-use pest::error::Error;
+use pest::error::{Error, ErrorVariant};
+use pest::iterators::Pair;
 use pest::Parser;
 
 use crate::ast::*;
+use crate::encoding::Encoding;
 use crate::index::bare::BareIdent;
 
+/// The largest numeral literal, as literals are elaborated to terms of size linear in their value.
+pub const MAX_NAT: usize = 10_000;
+
 #[derive(Parser)]
 #[grammar = "lambda.pest"]
 pub struct LambdaParser;
 
 pub fn parse(source: &str) -> result::Result<Term<BareIdent>, Error<Rule>> {
+    parse_with(source, Encoding::default())
+}
+
+/// Parse a term, elaborating its literals with `encoding`.
+pub fn parse_with(
+    source: &str,
+    encoding: Encoding,
+) -> result::Result<Term<BareIdent>, Error<Rule>> {
     let mut ast = vec![];
 
     let mut pairs = LambdaParser::parse(Rule::lambda, source)?;
@@ -20,7 +33,7 @@ pub fn parse(source: &str) -> result::Result<Term<BareIdent>, Error<Rule>> {
             let pairs = pair.into_inner();
             for pair in pairs {
                 match pair.as_rule() {
-                    Rule::expr => ast.push(parse_expr(pair.into_inner())),
+                    Rule::expr => ast.push(parse_expr(pair.into_inner(), encoding)?),
                     Rule::EOI => {}
                     _ => unreachable!(),
                 }
@@ -32,45 +45,160 @@ pub fn parse(source: &str) -> result::Result<Term<BareIdent>, Error<Rule>> {
     Ok(ast.first().unwrap().clone())
 }
 
-fn parse_expr<'a>(pairs: impl Iterator<Item = pest::iterators::Pair<'a, Rule>>) -> Term<BareIdent> {
+pub fn parse_module(source: &str) -> result::Result<Vec<Decl<BareIdent>>, Error<Rule>> {
+    let mut pairs = LambdaParser::parse(Rule::module, source)?;
     pairs
-        .into_iter()
-        .map(|term| parse_term(term.into_inner().next().unwrap()))
-        .fold(None, |pred, term| {
-            Some(match pred {
-                None => term,
-                Some(pred) => App(box pred, box term),
-            })
-        })
+        .next()
         .unwrap()
+        .into_inner()
+        .filter(|pair| pair.as_rule() != Rule::EOI)
+        .map(|pair| parse_decl(pair, Encoding::default()))
+        .collect()
 }
 
-fn parse_term(pair: pest::iterators::Pair<Rule>) -> Term<BareIdent> {
-    match pair.as_rule() {
-        Rule::var => Var(pair.as_str().to_string()),
-        Rule::app => parse_expr(pair.into_inner().next().unwrap().into_inner()),
-        Rule::abs => {
-            let mut pair = pair.into_inner();
-            let ident = pair.next().unwrap();
-            let ty = pair.next().unwrap().into_inner().next().unwrap();
-            let expr = pair.next().unwrap();
-            Abs(
-                ident.as_str().to_string(),
-                box parse_expr(ty.into_inner()),
-                box parse_expr(expr.into_inner()),
-            )
+fn parse_decl(
+    pair: Pair<Rule>,
+    encoding: Encoding,
+) -> result::Result<Decl<BareIdent>, Error<Rule>> {
+    let rule = pair.as_rule();
+    let mut pairs = pair.into_inner();
+    Ok(match rule {
+        Rule::def => {
+            let ident = pairs.next().unwrap().as_str().to_string();
+            let mut exprs = pairs
+                .map(|pair| parse_expr(pair.into_inner(), encoding))
+                .collect::<result::Result<Vec<_>, _>>()?;
+            let term = exprs.pop().unwrap();
+            Decl::Def(ident, exprs.pop(), term)
         }
-        Rule::pi => {
-            let mut pair = pair.into_inner();
-            let ident = pair.next().unwrap();
-            let lty = pair.next().unwrap().into_inner().next().unwrap();
-            let rty = pair.next().unwrap().into_inner().next().unwrap();
-            Pi(
-                ident.as_str().to_string(),
-                box parse_expr(lty.into_inner()),
-                box parse_expr(rty.into_inner()),
+        Rule::axiom => {
+            let ident = pairs.next().unwrap().as_str().to_string();
+            Decl::Axiom(
+                ident,
+                parse_expr(pairs.next().unwrap().into_inner(), encoding)?,
             )
         }
+        Rule::eval => {
+            let term = parse_expr(pairs.next().unwrap().into_inner(), encoding)?;
+            let strategy = match pairs.next() {
+                Some(pair) => Some(pair.as_str().parse().map_err(|message| {
+                    Error::new_from_span(ErrorVariant::CustomError { message }, pair.as_span())
+                })?),
+                None => None,
+            };
+            Decl::Eval(term, strategy)
+        }
+        Rule::check => Decl::Check(parse_expr(pairs.next().unwrap().into_inner(), encoding)?),
+        Rule::assert => Decl::Assert(
+            parse_expr(pairs.next().unwrap().into_inner(), encoding)?,
+            parse_expr(pairs.next().unwrap().into_inner(), encoding)?,
+        ),
+        _ => unreachable!(),
+    })
+}
+
+fn parse_expr<'a>(
+    mut pairs: impl Iterator<Item = Pair<'a, Rule>>,
+    encoding: Encoding,
+) -> result::Result<Term<BareIdent>, Error<Rule>> {
+    let spine = parse_spine(pairs.next().unwrap().into_inner(), encoding)?;
+    Ok(match pairs.next() {
+        Some(rhs) => Pi(
+            "_".to_string(),
+            box spine,
+            box parse_expr(rhs.into_inner(), encoding)?,
+        ),
+        None => spine,
+    })
+}
+
+fn parse_spine<'a>(
+    pairs: impl Iterator<Item = Pair<'a, Rule>>,
+    encoding: Encoding,
+) -> result::Result<Term<BareIdent>, Error<Rule>> {
+    let mut spine = None;
+    for term in pairs {
+        let term = parse_term(term.into_inner().next().unwrap(), encoding)?;
+        spine = Some(match spine {
+            None => term,
+            Some(pred) => App(box pred, box term),
+        });
+    }
+    Ok(spine.unwrap())
+}
+
+type Binder = fn(BareIdent, Box<Ty<BareIdent>>, Box<Term<BareIdent>>) -> Term<BareIdent>;
+
+/// Desugar a binder telescope, `bind`ing its variables from the innermost one outwards.
+fn parse_binders(
+    pair: Pair<Rule>,
+    bind: Binder,
+    encoding: Encoding,
+) -> result::Result<Term<BareIdent>, Error<Rule>> {
+    let mut pairs: Vec<_> = pair.into_inner().collect();
+    let body = parse_expr(pairs.pop().unwrap().into_inner(), encoding)?;
+    let mut binders = vec![];
+    for group in pairs {
+        let rule = group.as_rule();
+        let mut idents: Vec<_> = group.into_inner().collect();
+        let ty = match rule {
+            Rule::typed_binders => parse_expr(idents.pop().unwrap().into_inner(), encoding)?,
+            Rule::kind_binders => Kind(Kinds::Star),
+            _ => unreachable!(),
+        };
+        binders.extend(
+            idents
+                .into_iter()
+                .map(|ident| (ident.as_str().to_string(), ty.clone())),
+        );
+    }
+    Ok(binders
+        .into_iter()
+        .rev()
+        .fold(body, |body, (ident, ty)| bind(ident, box ty, box body)))
+}
+
+/// The value of a numeral literal, which is elaborated in unary.
+fn parse_nat(pair: &Pair<Rule>) -> result::Result<usize, Error<Rule>> {
+    match pair.as_str().parse() {
+        Ok(n) if n <= MAX_NAT => Ok(n),
+        _ => Err(Error::new_from_span(
+            ErrorVariant::CustomError {
+                message: format!("numerals are limited to {}", MAX_NAT),
+            },
+            pair.as_span(),
+        )),
+    }
+}
+
+fn parse_term(
+    pair: Pair<Rule>,
+    encoding: Encoding,
+) -> result::Result<Term<BareIdent>, Error<Rule>> {
+    Ok(match pair.as_rule() {
+        Rule::var => Var(pair.as_str().to_string()),
+        Rule::app => parse_expr(pair.into_inner().next().unwrap().into_inner(), encoding)?,
+        Rule::abs => parse_binders(pair, Abs, encoding)?,
+        Rule::pi | Rule::forall_pi => parse_binders(pair, Pi, encoding)?,
+        Rule::nat => encoding.nat(parse_nat(&pair)?),
+        Rule::boolean => encoding.boolean(pair.as_str() == "true"),
+        Rule::list => {
+            let mut elems = vec![];
+            let mut ty = None;
+            for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::expr => elems.push(parse_expr(pair.into_inner(), encoding)?),
+                    Rule::list_ty => {
+                        ty = Some(parse_expr(
+                            pair.into_inner().next().unwrap().into_inner(),
+                            encoding,
+                        )?)
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            encoding.list(elems, ty)
+        }
         Rule::kind => Term::Kind(match pair.as_str() {
             "*" => Kinds::Star,
             "□" => Kinds::Box,
@@ -78,5 +206,5 @@ fn parse_term(pair: pest::iterators::Pair<Rule>) -> Term<BareIdent> {
             _ => unreachable!(),
         }),
         _ => unreachable!(),
-    }
+    })
 }
@@ -0,0 +1,28 @@
+use crate::encoding::Encoding;
+use crate::env::Env;
+
+/// Combinators, and Church booleans, pairs, numerals and lists without types.
+pub const UNTYPED: &str = include_str!("untyped.lq");
+/// The typed counterparts of `UNTYPED`, which agree with the literals.
+pub const SYSTEM_F: &str = include_str!("system_f.lq");
+
+impl Env {
+    /// An environment with the untyped prelude loaded, where literals are untyped as well.
+    pub fn untyped_prelude() -> Env {
+        Env::with_prelude(UNTYPED, Encoding::Untyped)
+    }
+
+    /// An environment with the System F prelude loaded.
+    pub fn system_f_prelude() -> Env {
+        Env::with_prelude(SYSTEM_F, Encoding::Church)
+    }
+
+    fn with_prelude(source: &str, encoding: Encoding) -> Env {
+        let mut env = Env::default();
+        env.encoding = encoding;
+        if let Err(e) = env.load(source) {
+            panic!("prelude failed to load: {}", e);
+        }
+        env
+    }
+}
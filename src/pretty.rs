@@ -0,0 +1,362 @@
+use std::fmt::{self, Display, Write};
+
+use crate::ast::*;
+
+/// Line width used by `Display` when the formatter doesn't specify one, as in `{:40}`.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// A document in the style of Wadler's "prettier printer", which borrows its text.
+pub enum Doc<'a> {
+    Text(&'a dyn Display),
+    Owned(String),
+    /// A space, or a newline when the enclosing group doesn't fit.
+    Line,
+    /// Nothing, or a newline when the enclosing group doesn't fit.
+    Softline,
+    Nest(usize, Box<Doc<'a>>),
+    Concat(Vec<Doc<'a>>),
+    /// Lay out the document flat if it fits on the rest of the line, or break all of its lines.
+    Group(Box<Doc<'a>>),
+}
+
+use Doc::*;
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+type Cmd<'a, 'b> = (usize, Mode, &'b Doc<'a>);
+
+/// Counts the characters written to it.
+struct Measure(usize);
+
+impl Write for Measure {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
+
+fn measure(text: &dyn Display) -> usize {
+    let mut measure = Measure(0);
+    write!(measure, "{}", text).unwrap();
+    measure.0
+}
+
+/// Whether `next` fits into `rem` columns, along with the rest of the line from `rest`.
+fn fits(mut rem: isize, next: Cmd, rest: &[Cmd]) -> bool {
+    let mut stack = vec![next];
+    let mut rest = rest.iter().rev();
+    while rem >= 0 {
+        let (indent, mode, doc) = match stack.pop() {
+            Some(cmd) => cmd,
+            None => match rest.next() {
+                Some(cmd) => *cmd,
+                None => return true,
+            },
+        };
+        match doc {
+            Text(text) => rem -= measure(*text) as isize,
+            Owned(text) => rem -= measure(text) as isize,
+            Line | Softline if mode == Mode::Break => return true,
+            Line => rem -= 1,
+            Softline => {}
+            Nest(i, doc) => stack.push((indent + i, mode, doc)),
+            Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Group(doc) => stack.push((indent, mode, doc)),
+        }
+    }
+    false
+}
+
+impl<'a> Doc<'a> {
+    pub fn nest(indent: usize, doc: Doc<'a>) -> Self {
+        Nest(indent, box doc)
+    }
+
+    pub fn group(doc: Doc<'a>) -> Self {
+        Group(box doc)
+    }
+
+    /// Lay out the document within `width` columns where possible.
+    pub fn render(&self, width: usize, out: &mut impl Write) -> fmt::Result {
+        let mut col = 0;
+        let mut stack: Vec<Cmd> = vec![(0, Mode::Break, self)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Text(text) => {
+                    col += measure(*text);
+                    write!(out, "{}", text)?;
+                }
+                Owned(text) => {
+                    col += measure(text);
+                    out.write_str(text)?;
+                }
+                Line | Softline if mode == Mode::Break => {
+                    writeln!(out)?;
+                    write!(out, "{:indent$}", "", indent = indent)?;
+                    col = indent;
+                }
+                Line => {
+                    col += 1;
+                    out.write_char(' ')?;
+                }
+                Softline => {}
+                Nest(i, doc) => stack.push((indent + i, mode, doc)),
+                Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+                Group(doc) => {
+                    let flat = (indent, Mode::Flat, &**doc);
+                    let fits =
+                        mode == Mode::Flat || fits(width as isize - col as isize, flat, &stack);
+                    stack.push((indent, if fits { Mode::Flat } else { Mode::Break }, doc));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Concrete syntax of printed terms.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Syntax {
+    /// `\x:*.x`, `\pi A:*.A -> A` and `[]`, all accepted by `parse`.
+    Ascii,
+    /// `λx:*.x`, `πA:*.A → A` and `□`.
+    Unicode,
+    /// Math mode macros, `\lambda x{:}\ast.\,x`.
+    Latex,
+    /// `(lambda (x *) x)`, `(pi (A *) (-> A A))` and `box`.
+    SExpr,
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Syntax::Unicode
+    }
+}
+
+struct Symbols {
+    lambda: &'static str,
+    pi: &'static str,
+    colon: &'static str,
+    dot: &'static str,
+    arrow: &'static str,
+    /// Written before the space separating arguments.
+    app: &'static str,
+    star: &'static str,
+    square: &'static str,
+}
+
+static ASCII: Symbols = Symbols {
+    lambda: "\\",
+    pi: "\\pi ",
+    colon: ":",
+    dot: ".",
+    arrow: " ->",
+    app: "",
+    star: "*",
+    square: "[]",
+};
+
+static UNICODE: Symbols = Symbols {
+    lambda: "λ",
+    pi: "π",
+    colon: ":",
+    dot: ".",
+    arrow: " →",
+    app: "",
+    star: "*",
+    square: "□",
+};
+
+static LATEX: Symbols = Symbols {
+    lambda: "\\lambda ",
+    pi: "\\Pi ",
+    colon: "{:}",
+    dot: ".\\,",
+    arrow: " \\to",
+    app: "\\;",
+    star: "\\ast",
+    square: "\\square",
+};
+
+impl Syntax {
+    fn symbols(self) -> &'static Symbols {
+        match self {
+            Syntax::Ascii => &ASCII,
+            Syntax::Unicode => &UNICODE,
+            Syntax::Latex => &LATEX,
+            Syntax::SExpr => unreachable!("S-expressions have no infix syntax."),
+        }
+    }
+}
+
+pub(crate) fn parens(doc: Doc, parens: bool) -> Doc {
+    if parens {
+        Concat(vec![Text(&"("), Doc::nest(1, doc), Text(&")")])
+    } else {
+        doc
+    }
+}
+
+/// Print the variable `x`, multi-letter names are set upright in LaTeX.
+fn ident<T: IdentType>(x: &T, syntax: Syntax) -> Doc {
+    match syntax {
+        Syntax::Latex => {
+            let name = x.to_string().replace('_', "\\_");
+            if name.chars().count() > 1 {
+                Owned(format!("\\mathit{{{}}}", name))
+            } else {
+                Owned(name)
+            }
+        }
+        _ => Text(x),
+    }
+}
+
+/// An S-expression `(head docs...)`.
+pub(crate) fn list(docs: Vec<Doc>) -> Doc {
+    let mut docs = docs.into_iter();
+    let head = docs.next().unwrap();
+    let tail = docs.flat_map(|doc| vec![Line, doc]).collect();
+    parens(
+        Doc::group(Concat(vec![head, Doc::nest(1, Concat(tail))])),
+        true,
+    )
+}
+
+impl<T: IdentType> Term<T> {
+    /// The layout of the term, with as few parentheses as `parse` allows.
+    ///
+    /// Operands bind tighter as `prec` increases: 0 for binders and arrows, 1 for the head of
+    /// an application or the domain of an arrow, and 2 for arguments. Binders extend as far
+    /// to the right as possible, so they are only parenthesized when something follows them,
+    /// that is when they are not `last`.
+    pub fn to_doc(&self, syntax: Syntax, prec: usize, last: bool) -> Doc {
+        if syntax == Syntax::SExpr {
+            return self.to_sexpr();
+        }
+        let symbols = syntax.symbols();
+        match self {
+            Var(x) => ident(x, syntax),
+            Kind(Kinds::Star) => Text(&symbols.star),
+            Kind(Kinds::Box) => Text(&symbols.square),
+            App(_, _) => {
+                let mut args = vec![];
+                let mut head = self;
+                while let App(lhs, rhs) = head {
+                    args.push(rhs);
+                    head = lhs;
+                }
+                let last = last || prec > 1;
+                let args = args.iter().rev().enumerate().map(|(i, arg)| {
+                    Concat(vec![
+                        Text(&symbols.app),
+                        Line,
+                        arg.to_doc(syntax, 2, last && i + 1 == args.len()),
+                    ])
+                });
+                let spine = Concat(vec![
+                    head.to_doc(syntax, 1, false),
+                    Doc::nest(2, Concat(args.collect())),
+                ]);
+                parens(Doc::group(spine), prec > 1)
+            }
+            Pi(x, lty, rty) if !rty.fv().contains(x) => {
+                let last = last || prec > 0;
+                let arrow = Concat(vec![
+                    lty.to_doc(syntax, 1, false),
+                    Text(&symbols.arrow),
+                    Line,
+                    rty.to_doc(syntax, 0, last),
+                ]);
+                parens(Doc::group(arrow), prec > 0)
+            }
+            Abs(_, _, _) | Pi(_, _, _) => {
+                let parenthesized = prec > 0 && !last;
+                let mut binders = vec![];
+                let mut body = self;
+                loop {
+                    let (symbol, x, ty, e) = match body {
+                        // `\\pi:*` would read as a product.
+                        Abs(x, ty, e) if syntax == Syntax::Ascii && x.to_string() == "pi" => {
+                            (&"\\ ", x, ty, e)
+                        }
+                        Abs(x, ty, e) => (&symbols.lambda, x, ty, e),
+                        Pi(x, ty, e) if e.fv().contains(x) => (&symbols.pi, x, ty, e),
+                        _ => break,
+                    };
+                    binders.extend(vec![
+                        Text(symbol),
+                        ident(x, syntax),
+                        Text(&symbols.colon),
+                        ty.to_doc(syntax, 0, false),
+                        Text(&symbols.dot),
+                    ]);
+                    body = e;
+                }
+                binders.push(Doc::nest(
+                    2,
+                    Concat(vec![Softline, body.to_doc(syntax, 0, true)]),
+                ));
+                parens(Doc::group(Concat(binders)), parenthesized)
+            }
+        }
+    }
+
+    fn to_sexpr(&self) -> Doc {
+        match self {
+            Var(x) => Text(x),
+            Kind(Kinds::Star) => Text(&"*"),
+            Kind(Kinds::Box) => Text(&"box"),
+            App(_, _) => {
+                let mut spine = vec![];
+                let mut head = self;
+                while let App(lhs, rhs) = head {
+                    spine.push(rhs.to_sexpr());
+                    head = lhs;
+                }
+                spine.push(head.to_sexpr());
+                spine.reverse();
+                list(spine)
+            }
+            Pi(x, lty, rty) if !rty.fv().contains(x) => {
+                list(vec![Text(&"->"), lty.to_sexpr(), rty.to_sexpr()])
+            }
+            Abs(x, ty, e) | Pi(x, ty, e) => list(vec![
+                Text(if let Abs(_, _, _) = self {
+                    &"lambda"
+                } else {
+                    &"pi"
+                }),
+                list(vec![Text(x), ty.to_sexpr()]),
+                e.to_sexpr(),
+            ]),
+        }
+    }
+
+    /// Write the term directly to `out`, breaking lines longer than `width` where possible.
+    pub fn write_pretty(&self, syntax: Syntax, width: usize, out: &mut impl Write) -> fmt::Result {
+        self.to_doc(syntax, 0, true).render(width, out)
+    }
+
+    /// Display the term in `syntax` rather than the default Unicode.
+    pub fn display(&self, syntax: Syntax) -> Styled<'_, T> {
+        Styled { term: self, syntax }
+    }
+}
+
+/// A term displayed in a chosen syntax, at the formatter's width or `DEFAULT_WIDTH`.
+pub struct Styled<'a, T: IdentType> {
+    term: &'a Term<T>,
+    syntax: Syntax,
+}
+
+impl<T: IdentType> Display for Styled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = f.width().unwrap_or(DEFAULT_WIDTH);
+        self.term.write_pretty(self.syntax, width, f)
+    }
+}
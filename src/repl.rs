@@ -0,0 +1,289 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::{fs, io};
+
+use pest::error::Error;
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use crate::ast::*;
+use crate::blc::show_bits;
+use crate::encoding::Encoding;
+use crate::env::{Env, EnvError};
+use crate::index::bare::BareIdent;
+use crate::index::uid::{Conversion, TypeError, UID};
+use crate::parser::{parse, Rule};
+
+const HELP: &str = "\
+<term>                  reduce the term, like :reduce
+def, axiom, #eval, ...  declarations, as in .lq files
+:reduce <term>          reduce the term, printing every step
+:step [<term>]          reduce the term, or the last result, by one step
+:strategy [<strategy>]  show or set the reduction strategy
+:limit [<steps>]        show or set the step limit
+:type <term>            infer the type of the term
+:def <name> := <term>   define a name, a type may follow the name
+:load <file>            load the declarations of a file
+:alpha <a> <b>          whether two terms are alpha equivalent
+:equals <a> <b>         whether two terms are beta-eta convertible
+:show uid|bare|dbi      choose how terms are printed
+:blc <term>             the binary lambda calculus encoding and its size
+:help                   show this message
+:quit                   leave";
+
+/// How terms are printed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Show {
+    Uid,
+    Bare,
+    Dbi,
+}
+
+impl FromStr for Show {
+    type Err = ReplError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uid" => Ok(Show::Uid),
+            "bare" => Ok(Show::Bare),
+            "dbi" => Ok(Show::Dbi),
+            _ => Err(ReplError::Usage(":show uid|bare|dbi")),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplError {
+    Env(EnvError),
+    Io(String, io::Error),
+    UnknownCommand(String),
+    Invalid(String),
+    Usage(&'static str),
+}
+
+impl Display for ReplError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplError::Env(e) => write!(f, "{}", e),
+            ReplError::Io(path, e) => write!(f, "can't read `{}`: {}", path, e),
+            ReplError::UnknownCommand(cmd) => {
+                write!(f, "unknown command `:{}`, see :help", cmd)
+            }
+            ReplError::Invalid(message) => write!(f, "{}", message),
+            ReplError::Usage(usage) => write!(f, "usage: {}", usage),
+        }
+    }
+}
+
+impl From<EnvError> for ReplError {
+    fn from(e: EnvError) -> Self {
+        ReplError::Env(e)
+    }
+}
+
+impl From<Error<Rule>> for ReplError {
+    fn from(e: Error<Rule>) -> Self {
+        ReplError::Env(EnvError::Parse(e))
+    }
+}
+
+impl From<TypeError> for ReplError {
+    fn from(e: TypeError) -> Self {
+        ReplError::Env(EnvError::Type(e))
+    }
+}
+
+/// Split `a b` into its two operands, `b` being the last argument of the application.
+fn operands(
+    term: Term<BareIdent>,
+    usage: &'static str,
+) -> Result<(Term<BareIdent>, Term<BareIdent>), ReplError> {
+    match term {
+        App(a, b) => Ok((*a, *b)),
+        _ => Err(ReplError::Usage(usage)),
+    }
+}
+
+/// Whether `line` starts with the keyword `kw`, rather than with an identifier like `definitely`.
+fn keyword(line: &str, kw: &str) -> bool {
+    line.strip_prefix(kw).map_or(false, |rest| {
+        !rest.starts_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '\'' | '′'))
+    })
+}
+
+/// An interactive session, keeping its definitions and settings across lines.
+pub struct Repl {
+    pub env: Env,
+    strategy: ReduceStrategy,
+    show: Show,
+    /// Where `:step` continues from.
+    last: Option<Term<UID>>,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl {
+            env: Env::default(),
+            strategy: ReduceStrategy::NOR,
+            show: Show::Bare,
+            last: None,
+        }
+    }
+}
+
+impl Repl {
+    fn show(&self, term: &Term<UID>) -> String {
+        match self.show {
+            Show::Uid => term.to_string(),
+            Show::Bare => Term::<BareIdent>::from(term.clone()).to_string(),
+            Show::Dbi => Term::<BareIdent>::from(term.clone()).to_dbi().to_string(),
+        }
+    }
+
+    fn load(&mut self, source: &str) -> Result<String, ReplError> {
+        let responses = self.env.load(source)?;
+        Ok(responses
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn reduce(&mut self, source: &str) -> Result<String, ReplError> {
+        let term = self.env.elaborate(&parse(source)?);
+        let trace = term.trace(self.strategy, self.env.limit);
+        let mut lines: Vec<_> = trace.steps[1..]
+            .iter()
+            .map(|step| format!("--> {}", self.show(step)))
+            .collect();
+        let result = trace.steps.last().unwrap();
+        lines.push(match self.show {
+            Show::Bare => {
+                let value = Encoding::default().read(&Term::<BareIdent>::from(result.clone()));
+                format!("=> {}", value)
+            }
+            _ => format!("=> {}", self.show(result)),
+        });
+        if !trace.normal {
+            lines.push(format!("stopped after {} steps", trace.steps.len() - 1));
+        }
+        self.last = Some(result.clone());
+        Ok(lines.join("\n"))
+    }
+
+    fn step(&mut self, source: &str) -> Result<String, ReplError> {
+        let term = match source {
+            "" => self.last.clone().ok_or(ReplError::Usage(":step <term>"))?,
+            _ => self.env.elaborate(&parse(source)?),
+        };
+        let next = term.beta_reduce(self.strategy, Some(1));
+        let output = if next == term {
+            format!("{} is normal", self.show(&term))
+        } else {
+            format!("--> {}", self.show(&next))
+        };
+        self.last = Some(next);
+        Ok(output)
+    }
+
+    /// Run a single line, returning what should be printed.
+    pub fn eval(&mut self, line: &str) -> Result<String, ReplError> {
+        let line = line.trim();
+        let (cmd, arg) = match line.strip_prefix(':') {
+            Some(command) => match command.find(char::is_whitespace) {
+                Some(i) => (&command[..i], command[i..].trim()),
+                None => (command, ""),
+            },
+            None if line.is_empty() => return Ok(String::new()),
+            None if line.starts_with('#')
+                || ["def", "axiom"].iter().any(|kw| keyword(line, kw)) =>
+            {
+                return self.load(line)
+            }
+            None => return self.reduce(line),
+        };
+        match cmd {
+            "reduce" | "r" => self.reduce(arg),
+            "step" | "s" => self.step(arg),
+            "strategy" if arg.is_empty() => Ok(format!("{:?}", self.strategy)),
+            "strategy" => {
+                self.strategy = arg.parse().map_err(ReplError::Invalid)?;
+                Ok(String::new())
+            }
+            "limit" if arg.is_empty() => Ok(match self.env.limit {
+                Some(limit) => limit.to_string(),
+                None => "default".to_string(),
+            }),
+            "limit" => {
+                let limit = arg
+                    .parse()
+                    .map_err(|_| ReplError::Usage(":limit <steps>"))?;
+                self.env.limit = Some(limit);
+                Ok(String::new())
+            }
+            "type" | "t" => {
+                let ty = self.env.type_of(&parse(arg)?)?;
+                Ok(self.show(&ty))
+            }
+            "def" => self.load(&format!("def {}", arg)),
+            "load" | "l" => {
+                let source =
+                    fs::read_to_string(arg).map_err(|e| ReplError::Io(arg.to_string(), e))?;
+                self.load(&source)
+            }
+            "alpha" => {
+                let (a, b) = operands(parse(arg)?, ":alpha <a> <b>")?;
+                let bare = |term| Term::<BareIdent>::from(self.env.elaborate(term));
+                Ok(bare(&a).alpha_eq(&bare(&b)).to_string())
+            }
+            "equals" => {
+                let (a, b) = operands(parse(arg)?, ":equals <a> <b>")?;
+                let (a, b) = (self.env.elaborate(&a), self.env.elaborate(&b));
+                Ok(match a.convertible(&b, self.env.limit) {
+                    Conversion::Equal => "true".to_string(),
+                    Conversion::NotEqual => "false".to_string(),
+                    Conversion::Unknown(limit) => format!("unknown after {} steps", limit),
+                })
+            }
+            "blc" => {
+                let term = self.env.elaborate(&parse(arg)?);
+                let bits = term
+                    .to_blc()
+                    .map_err(|e| ReplError::Invalid(e.to_string()))?;
+                Ok(format!("{} ({} bits)", show_bits(&bits), bits.len()))
+            }
+            "show" => {
+                self.show = arg.parse()?;
+                Ok(String::new())
+            }
+            "help" | "h" | "?" => Ok(HELP.to_string()),
+            _ => Err(ReplError::UnknownCommand(cmd.to_string())),
+        }
+    }
+
+    /// Read lines from the terminal until `:quit` or end of input.
+    pub fn run(&mut self) {
+        let mut editor = Editor::<()>::new();
+        loop {
+            match editor.readline("λ> ") {
+                Ok(line) => {
+                    editor.add_history_entry(line.as_str());
+                    if matches!(line.trim(), ":quit" | ":q") {
+                        break;
+                    }
+                    match self.eval(&line) {
+                        Ok(output) if output.is_empty() => {}
+                        Ok(output) => println!("{}", output),
+                        Err(e) => eprintln!("error: {}", e),
+                    }
+                }
+                Err(ReadlineError::Interrupted) => {}
+                Err(ReadlineError::Eof) => break,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+}
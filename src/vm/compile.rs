@@ -0,0 +1,141 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::*;
+use crate::index::dbi::DBI;
+use crate::index::uid::UID;
+use crate::vm::Mode;
+
+#[derive(Clone, Debug)]
+pub enum Instr {
+    /// Push the value of the variable of De Bruijn index `i`, forcing it if it is a thunk.
+    Access(usize),
+    /// Push the variable of De Bruijn index `i` as it is, to pass it on as an argument.
+    Pass(usize),
+    /// Push a free variable, which can't be reduced any further.
+    Free(UID),
+    /// Push a closure of block `body`, whose binder is `name` and whose domain is computed by
+    /// block `domain`, only when reading the closure back.
+    Closure {
+        name: String,
+        domain: usize,
+        body: usize,
+    },
+    /// Pop a domain, and push a product over it with the codomain computed by block `body`.
+    Pi {
+        name: String,
+        body: usize,
+    },
+    Kind(Kinds),
+    /// Push a thunk of block `body`.
+    Delay(usize),
+    /// Pop a function then its argument, and call the function.
+    Apply,
+    /// Call like `Apply`, but return straight to the caller of the current block.
+    TailApply,
+    Return,
+}
+
+/// Compiled blocks of instructions. Each block ends with a `Return` or a `TailApply`.
+#[derive(Clone, Debug)]
+pub struct Code {
+    pub blocks: Vec<Vec<Instr>>,
+    pub entry: usize,
+}
+
+impl Code {
+    pub fn new(term: &Term<UID>, mode: Mode) -> Self {
+        let mut code = Code {
+            blocks: vec![],
+            entry: 0,
+        };
+        code.entry = code.block(&term.to_dbi(), mode);
+        code
+    }
+
+    /// Compile `term` into a block of its own and return its index.
+    fn block(&mut self, term: &Term<DBI<UID>>, mode: Mode) -> usize {
+        let mut instrs = vec![];
+        self.emit(term, mode, &mut instrs);
+        match instrs.last_mut() {
+            Some(last @ Instr::Apply) => *last = Instr::TailApply,
+            _ => instrs.push(Instr::Return),
+        }
+        self.blocks.push(instrs);
+        self.blocks.len() - 1
+    }
+
+    fn emit(&mut self, term: &Term<DBI<UID>>, mode: Mode, instrs: &mut Vec<Instr>) {
+        match term {
+            Var(DBI::Bound(i, _)) => instrs.push(Instr::Access(*i)),
+            Var(DBI::Free(x)) | Var(DBI::Binder(x)) => instrs.push(Instr::Free(x.clone())),
+            Abs(x, ty, e) => {
+                let domain = self.block(ty, mode);
+                let body = self.block(e, mode);
+                instrs.push(Instr::Closure {
+                    name: name(x),
+                    domain,
+                    body,
+                })
+            }
+            Pi(x, lty, rty) => {
+                self.emit(lty, mode, instrs);
+                let body = self.block(rty, mode);
+                instrs.push(Instr::Pi {
+                    name: name(x),
+                    body,
+                })
+            }
+            Kind(kind) => instrs.push(Instr::Kind(*kind)),
+            App(e1, e2) => {
+                match (mode, &**e2) {
+                    (Mode::Lazy, Var(DBI::Bound(i, _))) => instrs.push(Instr::Pass(*i)),
+                    (Mode::Lazy, App(_, _)) => {
+                        let arg = self.block(e2, mode);
+                        instrs.push(Instr::Delay(arg))
+                    }
+                    // Any other argument is cheaper to build right away than to delay.
+                    _ => self.emit(e2, mode, instrs),
+                }
+                self.emit(e1, mode, instrs);
+                instrs.push(Instr::Apply);
+            }
+        }
+    }
+}
+
+fn name(x: &DBI<UID>) -> String {
+    match x {
+        DBI::Binder(x) | DBI::Bound(_, x) | DBI::Free(x) => x.name.clone(),
+    }
+}
+
+impl Display for Instr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Instr::Access(i) => write!(f, "ACCESS {}", i),
+            Instr::Pass(i) => write!(f, "PASS {}", i),
+            Instr::Free(x) => write!(f, "FREE {}", x.name),
+            Instr::Closure { name, domain, body } => {
+                write!(f, "CLOSURE {} : #{} . #{}", name, domain, body)
+            }
+            Instr::Pi { name, body } => write!(f, "PI {} . #{}", name, body),
+            Instr::Kind(kind) => write!(f, "KIND {}", kind),
+            Instr::Delay(body) => write!(f, "DELAY #{}", body),
+            Instr::Apply => write!(f, "APPLY"),
+            Instr::TailApply => write!(f, "TAILAPPLY"),
+            Instr::Return => write!(f, "RETURN"),
+        }
+    }
+}
+
+/// One block per line, `#i: INSTR; INSTR; ...`, the entry block marked with `>`.
+impl Display for Code {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, block) in self.blocks.iter().enumerate() {
+            let marker = if i == self.entry { ">" } else { " " };
+            let instrs: Vec<_> = block.iter().map(ToString::to_string).collect();
+            writeln!(f, "{}#{}: {}", marker, i, instrs.join("; "))?;
+        }
+        Ok(())
+    }
+}
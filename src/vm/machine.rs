@@ -0,0 +1,273 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::ast::*;
+use crate::index::uid::{fresh_uid, UID};
+use crate::vm::{Code, Instr, VmError};
+
+/// Environments are shared linked lists, the innermost variable first.
+#[derive(Clone, Default)]
+struct Env(Option<Rc<(Value, Env)>>);
+
+#[derive(Clone)]
+enum Value {
+    Closure(Rc<Closure>),
+    /// A product, whose domain is already evaluated.
+    Pi(Rc<(Value, Closure)>),
+    Kind(Kinds),
+    /// A free variable applied to arguments.
+    Neutral(Rc<(UID, Vec<Value>)>),
+    Thunk(Rc<RefCell<Thunk>>),
+}
+
+#[derive(Clone)]
+struct Closure {
+    name: String,
+    domain: usize,
+    body: usize,
+    env: Env,
+}
+
+enum Thunk {
+    Delayed(usize, Env),
+    /// Being forced, so forcing it again would loop forever.
+    Forcing,
+    Forced(Value),
+}
+
+/// Where to go once the current block returns.
+enum Cont {
+    Return {
+        block: usize,
+        pc: usize,
+        env: Env,
+    },
+    /// Replace a thunk with the returned value.
+    Update(Rc<RefCell<Thunk>>),
+}
+
+pub struct Machine<'a> {
+    code: &'a Code,
+    stack: Vec<Value>,
+    conts: Vec<Cont>,
+    /// Beta steps left.
+    fuel: usize,
+    limit: usize,
+}
+
+fn lookup(env: &Env, i: usize) -> &Value {
+    let mut env = env;
+    for _ in 0..i {
+        env = &env.0.as_ref().unwrap().1;
+    }
+    &env.0.as_ref().unwrap().0
+}
+
+fn extend(value: Value, env: &Env) -> Env {
+    Env(Some(Rc::new((value, env.clone()))))
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(code: &'a Code, limit: usize) -> Self {
+        Machine {
+            code,
+            stack: vec![],
+            conts: vec![],
+            fuel: limit,
+            limit,
+        }
+    }
+
+    /// Run `block` in `env` to a value in weak head normal form.
+    fn run(&mut self, mut block: usize, mut env: Env) -> Result<Value, VmError> {
+        let base = self.conts.len();
+        let mut pc = 0;
+        loop {
+            let instr = &self.code.blocks[block][pc];
+            pc += 1;
+            match instr {
+                Instr::Access(i) => match lookup(&env, *i).clone() {
+                    Value::Thunk(thunk) => {
+                        let state = std::mem::replace(&mut *thunk.borrow_mut(), Thunk::Forcing);
+                        match state {
+                            Thunk::Forced(value) => {
+                                *thunk.borrow_mut() = Thunk::Forced(value.clone());
+                                self.stack.push(value);
+                            }
+                            Thunk::Delayed(body, thunk_env) => {
+                                self.conts.push(Cont::Return { block, pc, env });
+                                self.conts.push(Cont::Update(thunk));
+                                block = body;
+                                pc = 0;
+                                env = thunk_env;
+                            }
+                            // A term which needs its own value, like `(λx.x x) (λx.x x)`.
+                            Thunk::Forcing => return Err(VmError::Limit(self.limit)),
+                        }
+                    }
+                    value => self.stack.push(value),
+                },
+                Instr::Pass(i) => self.stack.push(lookup(&env, *i).clone()),
+                Instr::Free(x) => self
+                    .stack
+                    .push(Value::Neutral(Rc::new((x.clone(), vec![])))),
+                Instr::Closure { name, domain, body } => {
+                    self.stack.push(Value::Closure(Rc::new(Closure {
+                        name: name.clone(),
+                        domain: *domain,
+                        body: *body,
+                        env: env.clone(),
+                    })))
+                }
+                Instr::Pi { name, body } => {
+                    let domain = self.stack.pop().unwrap();
+                    let codomain = Closure {
+                        name: name.clone(),
+                        domain: 0,
+                        body: *body,
+                        env: env.clone(),
+                    };
+                    self.stack.push(Value::Pi(Rc::new((domain, codomain))))
+                }
+                Instr::Kind(kind) => self.stack.push(Value::Kind(*kind)),
+                Instr::Delay(body) => {
+                    self.stack
+                        .push(Value::Thunk(Rc::new(RefCell::new(Thunk::Delayed(
+                            *body,
+                            env.clone(),
+                        )))))
+                }
+                Instr::Apply | Instr::TailApply => {
+                    let function = self.stack.pop().unwrap();
+                    let arg = self.stack.pop().unwrap();
+                    match function {
+                        Value::Closure(closure) => {
+                            if self.fuel == 0 {
+                                return Err(VmError::Limit(self.limit));
+                            }
+                            self.fuel -= 1;
+                            if let Instr::Apply = instr {
+                                self.conts.push(Cont::Return { block, pc, env });
+                            }
+                            block = closure.body;
+                            pc = 0;
+                            env = extend(arg, &closure.env);
+                            continue;
+                        }
+                        Value::Neutral(neutral) => {
+                            let (x, args) = &*neutral;
+                            let mut args = args.clone();
+                            args.push(arg);
+                            self.stack.push(Value::Neutral(Rc::new((x.clone(), args))));
+                        }
+                        function => {
+                            let function = self.read_back(&function)?;
+                            return Err(VmError::NotAFunction(Term::from(function)));
+                        }
+                    }
+                    if let Instr::TailApply = instr {
+                        if let Some(value) = self.ret(&mut block, &mut pc, &mut env, base) {
+                            return Ok(value);
+                        }
+                    }
+                }
+                Instr::Return => {
+                    if let Some(value) = self.ret(&mut block, &mut pc, &mut env, base) {
+                        return Ok(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return from the current block, updating the thunks on the way, and give the value back
+    /// if `run` itself should return.
+    fn ret(
+        &mut self,
+        block: &mut usize,
+        pc: &mut usize,
+        env: &mut Env,
+        base: usize,
+    ) -> Option<Value> {
+        loop {
+            if self.conts.len() == base {
+                return self.stack.pop();
+            }
+            match self.conts.pop().unwrap() {
+                Cont::Update(thunk) => {
+                    *thunk.borrow_mut() = Thunk::Forced(self.stack.last().unwrap().clone());
+                }
+                Cont::Return {
+                    block: b,
+                    pc: p,
+                    env: e,
+                } => {
+                    *block = b;
+                    *pc = p;
+                    *env = e;
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// The value of a thunk, or the value itself.
+    fn force(&mut self, value: &Value) -> Result<Value, VmError> {
+        match value {
+            Value::Thunk(thunk) => {
+                let state = std::mem::replace(&mut *thunk.borrow_mut(), Thunk::Forcing);
+                let value = match state {
+                    Thunk::Forced(value) => value,
+                    Thunk::Delayed(body, env) => self.run(body, env)?,
+                    Thunk::Forcing => return Err(VmError::Limit(self.limit)),
+                };
+                *thunk.borrow_mut() = Thunk::Forced(value.clone());
+                Ok(value)
+            }
+            value => Ok(value.clone()),
+        }
+    }
+
+    /// Apply a closure to a fresh variable, and read both its domain and its body back.
+    fn read_back_closure(&mut self, closure: &Closure) -> Result<(UID, Term<UID>), VmError> {
+        let x = UID {
+            name: closure.name.clone(),
+            uid: fresh_uid(),
+        };
+        let var = Value::Neutral(Rc::new((x.clone(), vec![])));
+        let body = self.run(closure.body, extend(var, &closure.env))?;
+        Ok((x, self.read_back(&body)?))
+    }
+
+    /// The normal form of a value.
+    fn read_back(&mut self, value: &Value) -> Result<Term<UID>, VmError> {
+        match self.force(value)? {
+            Value::Closure(closure) => {
+                let domain = self.run(closure.domain, closure.env.clone())?;
+                let domain = self.read_back(&domain)?;
+                let (x, body) = self.read_back_closure(&closure)?;
+                Ok(Abs(x, box domain, box body))
+            }
+            Value::Pi(pi) => {
+                let (domain, codomain) = &*pi;
+                let domain = self.read_back(domain)?;
+                let (x, codomain) = self.read_back_closure(codomain)?;
+                Ok(Pi(x, box domain, box codomain))
+            }
+            Value::Kind(kind) => Ok(Kind(kind)),
+            Value::Neutral(neutral) => {
+                let (x, args) = &*neutral;
+                args.iter().try_fold(Var(x.clone()), |f, arg| {
+                    Ok(App(box f, box self.read_back(arg)?))
+                })
+            }
+            Value::Thunk(_) => unreachable!("Forced values aren't thunks."),
+        }
+    }
+
+    /// Run the entry block and read its value back.
+    pub fn normalize(&mut self) -> Result<Term<UID>, VmError> {
+        let value = self.run(self.code.entry, Env::default())?;
+        self.read_back(&value)
+    }
+}
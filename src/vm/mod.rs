@@ -0,0 +1,70 @@
+//! A bytecode virtual machine in the style of ZINC. Terms are compiled from their De Bruijn view
+//! into blocks of stack machine instructions, which run with environments of closures and, in
+//! the lazy mode, thunks which are updated with their value once forced.
+//!
+//! Results are read back into normal forms by applying closures to fresh variables and
+//! evaluating their bodies, that is normalization by evaluation, so only the readback reduces
+//! under binders. Types are evaluated like any other term, which keeps binder domains intact.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::ast::*;
+use crate::index::bare::BareIdent;
+use crate::index::uid::{DEFAULT_LIMIT, UID};
+
+pub use compile::{Code, Instr};
+
+mod compile;
+mod machine;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Mode {
+    /// Arguments are evaluated before the call, like `CBV` but under binders too.
+    Strict,
+    /// Arguments are evaluated when first needed, at most once.
+    Lazy,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(Mode::Strict),
+            "lazy" => Ok(Mode::Lazy),
+            _ => Err(format!("unknown evaluation mode `{}`", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum VmError {
+    /// More beta steps than the limit were needed to reach a normal form.
+    Limit(usize),
+    /// A product or a sort applied to an argument.
+    NotAFunction(Term<BareIdent>),
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::Limit(limit) => write!(f, "no normal form within {} steps", limit),
+            VmError::NotAFunction(term) => write!(f, "`{}` is applied but is not a function", term),
+        }
+    }
+}
+
+impl Term<UID> {
+    /// Compile the term, free variables being left as they are.
+    pub fn compile(&self, mode: Mode) -> Code {
+        Code::new(self, mode)
+    }
+
+    /// The normal form computed by the virtual machine, within `limit` beta steps, or
+    /// `DEFAULT_LIMIT` by default.
+    pub fn vm_nf(&self, mode: Mode, limit: Option<usize>) -> Result<Term<UID>, VmError> {
+        let code = self.compile(mode);
+        machine::Machine::new(&code, limit.unwrap_or(DEFAULT_LIMIT)).normalize()
+    }
+}